@@ -0,0 +1,84 @@
+//! Benchmarks of the solver's hot paths. Run with `cargo bench`, and compare
+//! against a saved baseline with
+//!   cargo bench -- --save-baseline before
+//!   cargo bench -- --baseline before
+//! The stats benchmark reports its progress on stderr, which can be hidden by
+//! redirecting it to /dev/null. The solver's internal `count_matching` is
+//! measured as the inner loop of the second guess benchmarks.
+
+use criterion::{BatchSize, Criterion, black_box, criterion_group, criterion_main};
+use hmode::{DEFAULT_START_WORD, Setter, Shard, Solver, SolverCaches, answers, solve_answers};
+
+// Answers giving a spread of first clues for the default start word, from
+// many remaining answers to few.
+const SECOND_GUESS_ANSWERS: [&[u8; 5]; 3] = [b"crook", b"batty", b"shine"];
+
+fn after_first_guess(answer: &[u8; 5]) -> Solver {
+    let mut solver = Solver::new(false);
+    let guess = solver.guess().unwrap();
+    solver.filter_self(Setter::from_word(*answer).check(guess));
+    solver
+}
+
+fn check(c: &mut Criterion) {
+    let setters: Vec<Setter> = answers()
+        .iter()
+        .take(100)
+        .map(|&w| Setter::from_word(w))
+        .collect();
+    c.bench_function("Setter::check", |b| {
+        b.iter(|| {
+            for setter in &setters {
+                black_box(setter.check(black_box(DEFAULT_START_WORD)));
+            }
+        })
+    });
+}
+
+fn filter(c: &mut Criterion) {
+    let solver = Solver::new(false);
+    let clues = Setter::from_word(*b"crook").check(DEFAULT_START_WORD);
+    c.bench_function("Solver::filter_self", |b| {
+        b.iter_batched(
+            || solver.clone(),
+            |mut solver| {
+                solver.filter_self(black_box(clues));
+                solver
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn second_guess(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Solver::guess second guess");
+    group.sample_size(10);
+    for answer in SECOND_GUESS_ANSWERS {
+        let solver = after_first_guess(answer);
+        let name = format!(
+            "{} ({} remaining)",
+            std::str::from_utf8(answer).unwrap(),
+            solver.remaining()
+        );
+        group.bench_function(name, |b| b.iter(|| solver.clone().guess().unwrap()));
+    }
+    group.finish();
+}
+
+fn reduced_stats(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stats");
+    group.sample_size(10);
+    let shard = Shard::new(1, 100).unwrap();
+    let start_word = std::str::from_utf8(&DEFAULT_START_WORD).unwrap();
+    group.bench_function("solve_answers 1/100", |b| {
+        b.iter(|| {
+            // A fresh cache each time, so that earlier runs' decisions aren't reused
+            let caches = SolverCaches::new(false);
+            solve_answers(start_word, false, Some(shard), &caches).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, check, filter, second_guess, reduced_stats);
+criterion_main!(benches);
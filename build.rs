@@ -24,6 +24,18 @@ fn list_to_static(words: Vec<String>) -> String {
     text
 }
 
+/// Generates a copy of the word list sorted alphabetically, with the position
+/// of each word in the original list, so that words can be found by binary search.
+fn sorted_index(words: &[String]) -> String {
+    let mut index: Vec<(&String, usize)> = words.iter().zip(0..).collect();
+    index.sort();
+    let mut text = String::with_capacity(words.len() * 20);
+    for (word, position) in index {
+        text.push_str(&format!("(*b\"{word}\", {position}), "));
+    }
+    text
+}
+
 /// Reads in a file containing answer words and a file containing probe words.
 /// The words in the two files are combined so that the answer words are at the
 /// front of the resulting list, with the probe words following. The function
@@ -50,6 +62,8 @@ fn main() -> Result<(), String> {
 
     let out_dir = env::var_os("OUT_DIR").ok_or("Could not read environment variable 'OUT_DIR'")?;
     let dest_path = Path::new(&out_dir).join("words-generated.rs");
+    let normal_index = sorted_index(&normal_words);
+    let alt_index = sorted_index(&alt_words);
     fs::write(
         dest_path,
         format!(
@@ -57,10 +71,14 @@ fn main() -> Result<(), String> {
 static ALL_WORDS: &[WdlWord] = &[{} ];
 static ALT_WORDS: &[WdlWord] = &[{} ];
 static ANSWER_WORDS_END : usize = {};
+static ALL_WORDS_INDEX: &[(WdlWord, u16)] = &[{} ];
+static ALT_WORDS_INDEX: &[(WdlWord, u16)] = &[{} ];
     "#,
             list_to_static(normal_words),
             list_to_static(alt_words),
-            answer_words_len
+            answer_words_len,
+            normal_index,
+            alt_index
         ),
     )
     .map_err(|e| format!("{e}"))?;
@@ -0,0 +1,16 @@
+//! Parses arbitrary text as each of hmode's file formats: game logs, rank
+//! checkpoints, second guess tables, stats results and decision caches. The
+//! parsers must reject bad input with an error rather than panic.
+
+#![no_main]
+
+use hmode::fuzzing;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    _ = fuzzing::parse_game_log(text);
+    _ = fuzzing::parse_checkpoint(text);
+    _ = fuzzing::parse_second_guesses(text);
+    _ = fuzzing::parse_results(text);
+    _ = fuzzing::parse_decisions(text);
+});
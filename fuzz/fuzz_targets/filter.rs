@@ -0,0 +1,38 @@
+//! Plays a solver through arbitrary guesses and clues, including clues that no
+//! answer could give, and checks that its next guess matches every clue, or
+//! that contradictory clues give an error rather than a panic. Each 3 bytes of
+//! input are the index of a guess word and a clue pattern index.
+
+#![no_main]
+
+use hmode::fuzzing::{PATTERN_COUNT, count_matching, pattern_from_index};
+use hmode::{Solver, all};
+use libfuzzer_sys::fuzz_target;
+
+// The probe search takes time proportional to the square of the remaining
+// answers, so positions with more than this are skipped to keep runs fast
+const MAX_SEARCHED: usize = 40;
+
+fuzz_target!(|data: &[u8]| {
+    let words = all(true);
+    let mut solver = Solver::new(true);
+    let mut history = Vec::new();
+    for chunk in data.chunks_exact(3) {
+        let guess = words[u16::from_le_bytes([chunk[0], chunk[1]]) as usize % words.len()];
+        let clues = pattern_from_index(guess, chunk[2] as usize % PATTERN_COUNT);
+        solver.record_guess(guess, clues);
+        history.push(clues);
+    }
+    if solver.guesses() == 0 || solver.remaining() > MAX_SEARCHED {
+        return;
+    }
+    match solver.guess() {
+        Ok(guess) => {
+            assert!(words.contains(&guess));
+            for &clues in &history {
+                assert_eq!(count_matching(&[guess], clues), 1);
+            }
+        }
+        Err(_) => assert_eq!(solver.remaining(), 0),
+    }
+});
@@ -0,0 +1,13 @@
+//! Parses arbitrary clue strings, as typed in assist mode, for a fixed guess.
+
+#![no_main]
+
+use hmode::{DEFAULT_START_WORD, parse_pattern, pattern_string};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|pattern: &str| {
+    if let Ok(result) = parse_pattern(DEFAULT_START_WORD, pattern) {
+        // A valid pattern must round trip
+        assert_eq!(pattern_string(&result), pattern.trim().to_ascii_uppercase());
+    }
+});
@@ -0,0 +1,48 @@
+/* C interface to the hmode Wordle solver, exported from the cdylib libhmode
+ * when it is built with the ffi feature.
+ *
+ * Functions that can fail return 0 on success, or the exit code of the error
+ * otherwise (see the README), and hmode_solver_last_error gives its message.
+ * A NULL solver or buffer gives 2, the code of an invalid argument.
+ *
+ *   HmodeSolver *solver = hmode_solver_new(false, true);
+ *   char guess[6];
+ *   hmode_solver_next_guess(solver, guess);       // "tares"
+ *   hmode_solver_submit(solver, guess, "--Y--");
+ *   hmode_solver_free(solver);
+ */
+#ifndef HMODE_H
+#define HMODE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct HmodeSolver HmodeSolver;
+
+/* Creates a solver for a new game. Release it with hmode_solver_free. */
+HmodeSolver *hmode_solver_new(bool alt_words, bool hard_mode);
+
+/* Records a guess and its clue pattern of G, Y and -, e.g. "--Y--". */
+int hmode_solver_submit(HmodeSolver *solver, const char *guess, const char *pattern);
+
+/* Writes the next guess to out, which must have room for 6 bytes. */
+int hmode_solver_next_guess(HmodeSolver *solver, char *out);
+
+/* The number of answer words that match the clues so far, or 0 for NULL. */
+size_t hmode_solver_remaining(const HmodeSolver *solver);
+
+/* The message of the last error, or an empty string if the last call
+ * succeeded. It is valid until the next call with the solver. */
+const char *hmode_solver_last_error(const HmodeSolver *solver);
+
+void hmode_solver_free(HmodeSolver *solver);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,180 @@
+use crate::colour::Renderer;
+use hmode::{CheckResult, HmodeError, RuleSet, Setter, Solver, WdlWord, is_solved, word_str};
+
+/// The analysis of a single turn of a game, comparing the player's guess with
+/// the guess the solver would have made in the same position.
+#[derive(Debug)]
+pub struct TurnAnalysis {
+    pub guess: WdlWord,
+    /// The clues for the guess
+    pub result: CheckResult,
+    /// Remaining possible answers before the guess
+    pub before: usize,
+    /// Remaining possible answers after the guess
+    pub after: usize,
+    /// The solver's guess, given the player's earlier guesses
+    pub best: WdlWord,
+    /// Remaining possible answers if the solver's guess had been played
+    pub best_after: usize,
+    /// A description of the hard mode rule broken by the guess, if any
+    pub hard_mode_violation: Option<String>,
+}
+
+/// Information gained, in bits, by reducing the answer list from `before` to `after` words.
+pub fn information(before: usize, after: usize) -> f64 {
+    if before == 0 || after == 0 {
+        return 0.0;
+    }
+    (before as f64 / after as f64).log2()
+}
+
+impl TurnAnalysis {
+    pub fn bits(&self) -> f64 {
+        information(self.before, self.after)
+    }
+
+    pub fn best_bits(&self) -> f64 {
+        information(self.before, self.best_after)
+    }
+}
+
+#[derive(Debug)]
+pub struct GameAnalysis {
+    pub target: WdlWord,
+    pub turns: Vec<TurnAnalysis>,
+}
+
+/// Analyses a finished game turn by turn, reporting guesses that break the
+/// given rules. Guesses after the target has been found are ignored.
+pub fn analyse_game(
+    target: WdlWord,
+    guesses: &[WdlWord],
+    alt_words: bool,
+    rules: RuleSet,
+) -> Result<GameAnalysis, HmodeError> {
+    let setter = Setter::from_word(target);
+    let mut solver = Solver::new(alt_words);
+    let mut history = Vec::new();
+    let mut turns = Vec::new();
+
+    for &guess in guesses {
+        let before = solver.remaining();
+
+        let mut alternative = solver.clone();
+        // guess() has already counted the guess, so only the clues are applied
+        let best = alternative.guess()?;
+        alternative.filter_self(setter.check(best));
+
+        let result = setter.check(guess);
+        let hard_mode_violation = rules.violation(&history, guess);
+        solver.record_guess(guess, result);
+        history.push(result);
+
+        turns.push(TurnAnalysis {
+            guess,
+            result,
+            before,
+            after: solver.remaining(),
+            best,
+            best_after: alternative.remaining(),
+            hard_mode_violation,
+        });
+        if is_solved(&result) {
+            break;
+        }
+    }
+    Ok(GameAnalysis { target, turns })
+}
+
+impl std::fmt::Display for GameAnalysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.render(Renderer::plain()))
+    }
+}
+
+impl GameAnalysis {
+    /// Formats the analysis as a table, with the player's guesses coloured by
+    /// their clues if the renderer uses colour.
+    pub fn render(&self, renderer: Renderer) -> String {
+        let mut f = String::new();
+        // Writing to a String can't fail
+        _ = self.write_table(&mut f, renderer);
+        f
+    }
+
+    fn write_table(&self, f: &mut impl std::fmt::Write, renderer: Renderer) -> std::fmt::Result {
+        writeln!(f, "Target : {}\n", word_str(&self.target))?;
+        writeln!(
+            f,
+            "Turn  Guess  Before  After   Bits | hmode  After   Bits   Gain"
+        )?;
+        for (i, turn) in self.turns.iter().enumerate() {
+            writeln!(
+                f,
+                "{:4}  {}  {:6}  {:5}  {:5.2} | {}  {:5}  {:5.2}  {:+5.2}",
+                i + 1,
+                renderer.word(&turn.result),
+                turn.before,
+                turn.after,
+                turn.bits(),
+                word_str(&turn.best),
+                turn.best_after,
+                turn.best_bits(),
+                turn.best_bits() - turn.bits()
+            )?;
+            if let Some(violation) = &turn.hard_mode_violation {
+                writeln!(f, "      Hard mode violation : {violation}")?;
+            }
+        }
+        match self.turns.last() {
+            Some(turn) if turn.guess == self.target => {
+                writeln!(f, "\nSolved in {} guesses", self.turns.len())
+            }
+            _ => writeln!(f, "\nNot solved"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn information_is_log2_of_reduction() {
+        assert_eq!(information(8, 1), 3.0);
+        assert_eq!(information(8, 8), 0.0);
+        assert_eq!(information(0, 0), 0.0);
+    }
+
+    #[test]
+    fn analyse_game_follows_guesses() {
+        let analysis = analyse_game(
+            *b"crook",
+            &[*b"tares", *b"brood", *b"crook"],
+            false,
+            RuleSet::Hard,
+        )
+        .unwrap();
+        assert_eq!(analysis.turns.len(), 3);
+        assert_eq!(analysis.turns[0].before, 2315);
+        assert_eq!(analysis.turns[0].best, *b"tares");
+        assert_eq!(analysis.turns[0].after, analysis.turns[0].best_after);
+        assert_eq!(analysis.turns[1].before, analysis.turns[0].after);
+        assert_eq!(analysis.turns[2].after, 1);
+        assert!(
+            analysis
+                .turns
+                .iter()
+                .all(|t| t.hard_mode_violation.is_none())
+        );
+    }
+
+    #[test]
+    fn analyse_game_reports_hard_mode_violations() {
+        let guesses = [*b"tares", *b"pluck", *b"crook"];
+        let analysis = analyse_game(*b"crook", &guesses, false, RuleSet::Hard).unwrap();
+        assert!(analysis.turns[1].hard_mode_violation.is_some());
+        let analysis = analyse_game(*b"crook", &guesses, false, RuleSet::Normal).unwrap();
+        assert!(analysis.turns[1].hard_mode_violation.is_none());
+    }
+}
@@ -0,0 +1,93 @@
+use crate::error::HmodeError;
+use crate::setter::{self, CheckResult, RuleSet};
+use crate::solver::Solver;
+use crate::stats::SolverCaches;
+use crate::words::{self, WdlWord};
+
+/// Suggests guesses for a game played elsewhere, from the guesses made and the
+/// clue patterns they were given, as the assist command does. This is the
+/// solver used by the bindings for other languages.
+#[derive(Clone)]
+pub struct Assistant {
+    solver: Solver,
+    alt_words: bool,
+    rules: RuleSet,
+    history: Vec<CheckResult>,
+}
+
+impl Assistant {
+    /// Creates an assistant for a new game, checking the guesses against the
+    /// given rules.
+    pub fn new(alt_words: bool, rules: RuleSet) -> Self {
+        Assistant {
+            solver: Solver::new(alt_words),
+            alt_words,
+            rules,
+            history: Vec::new(),
+        }
+    }
+
+    /// Uses the caches shared by the solvers in a run, before any clues are fed.
+    pub fn with_caches(mut self, caches: &SolverCaches) -> Self {
+        self.solver = caches.solver(self.alt_words);
+        self
+    }
+
+    /// Records a guess and its clue pattern of G, Y and -, e.g. "--Y--". A
+    /// clue that breaks the rules or leaves no possible answer is rejected,
+    /// and the assistant is left as it was.
+    pub fn feed_clue(&mut self, guess: &str, pattern: &str) -> Result<CheckResult, HmodeError> {
+        let guess = words::to_static_word(guess, false, self.alt_words)?;
+        let result = setter::parse_pattern(guess, pattern)?;
+        self.rules.check(&self.history, guess)?;
+        let mut next = self.solver.clone();
+        next.record_guess(guess, result);
+        next.check_clues()?;
+        self.solver = next;
+        self.history.push(result);
+        Ok(result)
+    }
+
+    /// The guess the solver would make next.
+    pub fn next_guess(&self) -> Result<WdlWord, HmodeError> {
+        self.solver.clone().guess()
+    }
+
+    /// The solver's best guesses, best first.
+    pub fn suggestions(&self, count: usize) -> Result<Vec<WdlWord>, HmodeError> {
+        self.solver.suggestions(count)
+    }
+
+    /// The number of answer words that match the clues so far.
+    pub fn remaining(&self) -> usize {
+        self.solver.remaining()
+    }
+
+    /// The clues fed in so far, in order.
+    pub fn history(&self) -> &[CheckResult] {
+        &self.history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feeding_clues_narrows_suggestions() {
+        let mut assistant = Assistant::new(false, RuleSet::Hard);
+        assert_eq!(assistant.next_guess().unwrap(), *b"tares");
+        assistant.feed_clue("TARES", "--Y--").unwrap();
+        assert!(assistant.remaining() < 100);
+        let suggestions = assistant.suggestions(3).unwrap();
+        assert_eq!(suggestions.len(), 3);
+        assert_eq!(assistant.next_guess().unwrap(), suggestions[0]);
+        // A rejected clue leaves the assistant unchanged
+        let remaining = assistant.remaining();
+        assert!(assistant.feed_clue("tares", "GGGGG").is_err());
+        // Hard mode needs the R
+        assert!(assistant.feed_clue("pious", "-----").is_err());
+        assert_eq!(assistant.remaining(), remaining);
+        assert_eq!(assistant.history().len(), 1);
+    }
+}
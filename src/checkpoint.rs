@@ -0,0 +1,167 @@
+use crate::error::HmodeError;
+use crate::line_file;
+use crate::shard::Shard;
+use std::fs;
+use std::time::{Duration, Instant};
+
+// A checkpoint is a line file holding the score for every start word evaluated
+// so far, in the form
+//   score <word> <average reduction>
+// The checkpoint of a finished run is also its result file, which can be
+// merged with the results of other shards.
+pub const CHECKPOINT_HEADER: &str = "# hmode start word checkpoint";
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, PartialEq)]
+pub struct StartWordRanking(pub String, pub f32);
+
+/// Periodically saves the scores from a start word search, so that an
+/// interrupted search can be resumed without scoring the same words again.
+pub struct Checkpoint {
+    path: String,
+    alt_words: bool,
+    shard: Option<Shard>,
+    last_saved: Instant,
+}
+
+/// The contents of a checkpoint file.
+#[derive(Debug, PartialEq)]
+pub struct Scores {
+    pub alt_words: bool,
+    pub shard: Option<Shard>,
+    pub rankings: Vec<StartWordRanking>,
+}
+
+impl Scores {
+    pub fn load(path: &str) -> Result<Self, HmodeError> {
+        line_file::load(path, parse)
+    }
+}
+
+impl Checkpoint {
+    pub fn new(path: &str, alt_words: bool, shard: Option<Shard>) -> Self {
+        Checkpoint {
+            path: path.into(),
+            alt_words,
+            shard,
+            last_saved: Instant::now(),
+        }
+    }
+
+    /// Loads the scores saved in an earlier run, or none if the checkpoint
+    /// doesn't exist yet, e.g. because the run stopped before its first save.
+    /// The checkpoint must have been written using the same word list and shard.
+    pub fn load(&self) -> Result<Vec<StartWordRanking>, HmodeError> {
+        if !self.exists()? {
+            return Ok(Vec::new());
+        }
+        let scores = Scores::load(&self.path)?;
+        if scores.alt_words != self.alt_words || scores.shard != self.shard {
+            return Err(HmodeError::invalid_file(
+                &self.path,
+                "the checkpoint was written with a different word list or shard",
+            ));
+        }
+        Ok(scores.rankings)
+    }
+
+    /// Checks that a new run won't replace the checkpoint of an earlier run,
+    /// which should be resumed or removed instead.
+    pub fn check_unused(&self) -> Result<(), HmodeError> {
+        if self.exists()? {
+            return Err(HmodeError::InvalidArgument(format!(
+                "The checkpoint file {} already exists. Use --resume to continue its run, \
+                 or remove it to start again",
+                self.path
+            )));
+        }
+        Ok(())
+    }
+
+    fn exists(&self) -> Result<bool, HmodeError> {
+        fs::exists(&self.path)
+            .map_err(|e| HmodeError::io(format!("reading file {}", self.path), e))
+    }
+
+    /// Saves the scores, replacing the previous checkpoint.
+    pub fn save(&mut self, rankings: &[StartWordRanking]) -> Result<(), HmodeError> {
+        let mut text = format!("{CHECKPOINT_HEADER}\nalt-words {}\n", self.alt_words);
+        if let Some(shard) = self.shard {
+            text.push_str(&format!("shard {shard}\n"));
+        }
+        for ranking in rankings {
+            text.push_str(&format!("score {} {}\n", ranking.0, ranking.1));
+        }
+        line_file::save(&self.path, &text)?;
+        self.last_saved = Instant::now();
+        Ok(())
+    }
+
+    /// Saves the scores if enough time has passed since the last save.
+    pub fn save_if_due(&mut self, rankings: &[StartWordRanking]) -> Result<(), HmodeError> {
+        if self.last_saved.elapsed() >= SAVE_INTERVAL {
+            self.save(rankings)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn parse(text: &str) -> Result<Scores, String> {
+    let mut scores = Scores {
+        alt_words: false,
+        shard: None,
+        rankings: Vec::new(),
+    };
+    for line in line_file::lines(text) {
+        match line.fields.as_slice() {
+            ["alt-words", alt] => {
+                scores.alt_words = alt
+                    .parse()
+                    .map_err(|_| line.error(format!("invalid value '{alt}'")))?;
+            }
+            ["shard", shard] => {
+                scores.shard = Some(shard.parse().map_err(|e: HmodeError| line.error(e))?)
+            }
+            ["score", word, score] => scores.rankings.push(StartWordRanking(
+                word.to_string(),
+                score
+                    .parse()
+                    .map_err(|_| line.error(format!("invalid score '{score}'")))?,
+            )),
+            _ => return Err(line.unrecognised()),
+        }
+    }
+    Ok(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("hmode-checkpoint-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let rankings = vec![
+            StartWordRanking("tares".into(), 2203.125),
+            StartWordRanking("crane".into(), 2180.3),
+        ];
+        let shard = Some(Shard::new(2, 3).unwrap());
+        let mut checkpoint = Checkpoint::new(path, true, shard);
+        assert_eq!(checkpoint.load(), Ok(Vec::new()));
+        assert!(checkpoint.check_unused().is_ok());
+        checkpoint.save(&rankings).unwrap();
+        assert!(checkpoint.check_unused().is_err());
+        assert_eq!(checkpoint.load(), Ok(rankings));
+        assert_eq!(Scores::load(path).unwrap().shard, shard);
+        assert!(Checkpoint::new(path, false, shard).load().is_err());
+        assert!(Checkpoint::new(path, true, None).load().is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn parse_rejects_bad_scores() {
+        assert!(parse("score tares lots").is_err());
+        assert!(parse("tares 2203.1").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use hmode::{CheckResult, Clue, HmodeError, guessed_word, pattern_string};
+use std::io::IsTerminal;
+
+/// The tile colours for each kind of clue.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Palette {
+    /// Green, yellow and grey, as in Wordle
+    Standard,
+    /// Orange and blue, as in Wordle's high contrast mode, for colour blind users
+    HighContrast,
+}
+
+impl Palette {
+    /// The 256-colour terminal palette index of the background for a clue.
+    pub fn background(&self, clue: Clue) -> u8 {
+        match (self, clue) {
+            (Palette::Standard, Clue::Right(_)) => 34,
+            (Palette::Standard, Clue::Elsewhere(_)) => 178,
+            (Palette::HighContrast, Clue::Right(_)) => 208,
+            (Palette::HighContrast, Clue::Elsewhere(_)) => 39,
+            (_, Clue::Wrong(_)) => 240,
+        }
+    }
+
+    /// The 256-colour terminal palette index of the letter for a clue.
+    pub fn foreground(&self, clue: Clue) -> u8 {
+        match clue {
+            Clue::Wrong(_) => 255,
+            _ => 16,
+        }
+    }
+}
+
+impl std::str::FromStr for Palette {
+    type Err = HmodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" => Ok(Palette::Standard),
+            "high-contrast" => Ok(Palette::HighContrast),
+            _ => Err(HmodeError::InvalidArgument(format!(
+                "Unknown palette '{s}'. Use 'standard' or 'high-contrast'"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for Palette {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Palette::Standard => write!(f, "standard"),
+            Palette::HighContrast => write!(f, "high-contrast"),
+        }
+    }
+}
+
+/// Displays the guessed word of a CheckResult with each letter coloured by its
+/// clue, using ANSI escape codes. Each tile is one character wide, so coloured
+/// output lines up in the same columns as plain output.
+pub struct Tiles<'a> {
+    pub result: &'a CheckResult,
+    pub palette: Palette,
+}
+
+impl std::fmt::Display for Tiles<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for &clue in self.result {
+            let (Clue::Right(c) | Clue::Elsewhere(c) | Clue::Wrong(c)) = clue;
+            write!(
+                f,
+                "\x1b[1;38;5;{};48;5;{}m{}\x1b[0m",
+                self.palette.foreground(clue),
+                self.palette.background(clue),
+                c as char
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Chooses between coloured tiles and plain text for clues in command output.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Renderer {
+    palette: Option<Palette>,
+}
+
+impl Renderer {
+    /// Uses colour if stdout is a terminal and the NO_COLOR environment
+    /// variable isn't set (see https://no-color.org).
+    pub fn detect(palette: Palette) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        if std::io::stdout().is_terminal() && !no_color {
+            Renderer::coloured(palette)
+        } else {
+            Renderer::plain()
+        }
+    }
+
+    pub fn coloured(palette: Palette) -> Self {
+        Renderer {
+            palette: Some(palette),
+        }
+    }
+
+    pub fn plain() -> Self {
+        Renderer { palette: None }
+    }
+
+    pub fn is_coloured(&self) -> bool {
+        self.palette.is_some()
+    }
+
+    /// Shows the guessed word, coloured by its clues if colour is used.
+    pub fn word(&self, result: &CheckResult) -> String {
+        match self.palette {
+            Some(palette) => Tiles { result, palette }.to_string(),
+            None => String::from_utf8_lossy(&guessed_word(result)).into_owned(),
+        }
+    }
+
+    /// Shows the clues as coloured tiles, or as a pattern string such as
+    /// "-GY--" without colour.
+    pub fn pattern(&self, result: &CheckResult) -> String {
+        match self.palette {
+            Some(palette) => Tiles { result, palette }.to_string(),
+            None => pattern_string(result),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hmode::Setter;
+
+    #[test]
+    fn plain_renderer_matches_plain_output() {
+        let result = Setter::from_word(*b"maybe").check(*b"cable");
+        assert_eq!(Renderer::plain().word(&result), "cable");
+        assert_eq!(Renderer::plain().pattern(&result), "-GY-G");
+    }
+
+    #[test]
+    fn tiles_use_palette_colours() {
+        let result = Setter::from_word(*b"maybe").check(*b"cable");
+        let standard = Renderer::coloured(Palette::Standard).word(&result);
+        assert!(standard.starts_with("\x1b[1;38;5;255;48;5;240mc\x1b[0m"));
+        assert!(standard.contains("48;5;34ma"));
+        let high_contrast = Renderer::coloured(Palette::HighContrast).word(&result);
+        assert!(high_contrast.contains("48;5;208ma"));
+        assert!(high_contrast.contains("48;5;39mb"));
+        assert_eq!("High-Contrast".parse(), Ok(Palette::HighContrast));
+    }
+}
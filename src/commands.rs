@@ -0,0 +1,665 @@
+//! The commands of the hmode command line tool. The tool parses its arguments
+//! in main.rs, and runs the command with the functions here.
+
+use crate::analyse;
+use crate::colour::{Palette, Renderer};
+use crate::date;
+use hmode::{
+    Assistant, Checkpoint, Clue, DecisionCache, FullGameRanking, GameLog, HmodeError, MAX_GUESSES,
+    Merged, Recorder, RuleSet, SecondGuessTable, Setter, Shard, Solver, SolverCaches,
+    StartWordRanking, Stats, StatsResults, find_optimal_start_word, guessed_word, is_solved,
+    pattern_string, rank_full_games, solve_answers, to_static_word, word_str,
+};
+use serde_json::json;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// The global options, and the caches shared by the solvers in a run.
+pub struct Options {
+    alt_words: bool,
+    rules: RuleSet,
+    format: Format,
+    // Only the terminal interface uses the palette when there is no terminal
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    palette: Palette,
+    renderer: Renderer,
+    caches: SolverCaches,
+    decision_cache: Option<String>,
+}
+
+impl Options {
+    pub fn new(alt_words: bool, rules: RuleSet, format: Format, palette: Palette) -> Self {
+        Options {
+            alt_words,
+            rules,
+            format,
+            palette,
+            renderer: Renderer::detect(palette),
+            caches: SolverCaches {
+                saved_second_guesses: true,
+                ..SolverCaches::new(alt_words)
+            },
+            decision_cache: None,
+        }
+    }
+
+    /// Looks up the solver's second guess in the table saved to the given file,
+    /// instead of the one in the cache directory.
+    pub fn with_second_guess_cache(mut self, path: Option<&str>) -> Result<Self, HmodeError> {
+        let Some(path) = path else {
+            return Ok(self);
+        };
+        let table = SecondGuessTable::load(path)?;
+        if table.alt_words != self.alt_words {
+            eprintln!(
+                "Warning : the second guess table was built for a different word list, and will not be used"
+            );
+        }
+        self.caches.second_guesses = Some(Arc::new(table));
+        Ok(self)
+    }
+
+    /// Loads the solver's earlier decisions from the given file, if it exists.
+    /// They are saved back by save_decisions.
+    pub fn with_decision_cache(mut self, path: Option<String>) -> Result<Self, HmodeError> {
+        if let Some(path) = &path {
+            self.caches.decisions = Arc::new(DecisionCache::load_or_new(path, self.alt_words)?);
+        }
+        self.decision_cache = path;
+        Ok(self)
+    }
+
+    /// Saves the solver's decisions to the decision cache, if one was given
+    /// and decisions were added during the run.
+    pub fn save_decisions(&self) -> Result<(), HmodeError> {
+        let decisions = &self.caches.decisions;
+        if let Some(path) = &self.decision_cache
+            && decisions.is_changed()
+        {
+            decisions.save(path)?;
+            eprintln!("Saved {} solver decisions to {path}", decisions.len());
+        }
+        Ok(())
+    }
+
+    fn text_only(&self, command: &str) -> Result<(), HmodeError> {
+        match self.format {
+            Format::Text => Ok(()),
+            Format::Json => Err(HmodeError::InvalidArgument(format!(
+                "The {command} command only supports text output"
+            ))),
+        }
+    }
+
+    fn hard_mode_only(&self, command: &str) -> Result<(), HmodeError> {
+        match self.rules {
+            RuleSet::Hard => Ok(()),
+            RuleSet::Normal => Err(HmodeError::InvalidArgument(format!(
+                "The {command} command only supports the hard mode rules"
+            ))),
+        }
+    }
+}
+
+/// Installs a Ctrl-C handler that asks long runs to stop at the next item, so
+/// that they can report the results gathered so far. A second Ctrl-C exits
+/// immediately.
+fn install_interrupt_handler() -> Result<(), HmodeError> {
+    ctrlc::set_handler(|| {
+        if hmode::cancel() {
+            std::process::exit(130);
+        }
+    })
+    .map_err(|e| HmodeError::io("installing the Ctrl-C handler", std::io::Error::other(e)))
+}
+
+/// Fails with an interrupted error if Ctrl-C stopped the run, so that scripts
+/// can tell the partial results already shown from complete ones.
+fn check_interrupted(command: &str) -> Result<(), HmodeError> {
+    if hmode::cancelled() {
+        Err(HmodeError::Interrupted(format!(
+            "The {command} run was interrupted, so its results are partial"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Calculates the statistics of full games for a start word, optionally
+/// writing each answer's number of guesses to a results file.
+pub fn stats(
+    options: &Options,
+    start_word: &str,
+    shard: Option<Shard>,
+    output: Option<&str>,
+) -> Result<(), HmodeError> {
+    options.hard_mode_only("stats")?;
+    let start_word = to_static_word(start_word, false, options.alt_words)?;
+    let start_word = word_str(&start_word).to_string();
+    if options.format == Format::Text {
+        println!(
+            "Calculating statistics for start word \"{start_word}\". This may take some time."
+        );
+    }
+    if let Some(table) = &options.caches.second_guesses
+        && word_str(&table.start_word) != start_word
+    {
+        eprintln!(
+            "Warning : the second guess table was built for start word {}, and will not be used",
+            word_str(&table.start_word)
+        );
+    }
+    install_interrupt_handler()?;
+    let solves = solve_answers(
+        start_word.as_str(),
+        options.alt_words,
+        shard,
+        &options.caches,
+    )?;
+    let stats = Stats::from_solves(&solves);
+    if let Some(path) = output {
+        StatsResults {
+            start_word,
+            alt_words: options.alt_words,
+            shard,
+            complete: !hmode::cancelled(),
+            solves,
+        }
+        .save(path)?;
+    }
+    print_stats(&stats, options.format);
+    check_interrupted("stats")
+}
+
+fn print_stats(stats: &Stats, format: Format) {
+    match format {
+        Format::Text => println!("{stats}"),
+        Format::Json => println!("{}", stats.to_json()),
+    }
+}
+
+/// Ranks the start words, optionally saving the scores to a checkpoint as it
+/// goes and ranking a shortlist again by full games.
+pub fn rank(
+    options: &Options,
+    checkpoint: Option<&str>,
+    resume: bool,
+    shard: Option<Shard>,
+    shortlist: Option<usize>,
+) -> Result<(), HmodeError> {
+    options.hard_mode_only("rank")?;
+    if options.format == Format::Text {
+        println!("Finding optimal start word. This may take several minutes.");
+    }
+    install_interrupt_handler()?;
+    let checkpoint = checkpoint.map(|path| Checkpoint::new(path, options.alt_words, shard));
+    let mut rankings = find_optimal_start_word(options.alt_words, shard, checkpoint, resume)?;
+    match shortlist {
+        Some(size) if !hmode::cancelled() => {
+            rankings.truncate(size);
+            let full = rank_full_games(&rankings, options.alt_words, &options.caches)?;
+            print_full_game_rankings(&full, options.format);
+        }
+        _ => print_rankings(&rankings, options.format),
+    }
+    check_interrupted("rank")
+}
+
+fn print_rankings(rankings: &[StartWordRanking], format: Format) {
+    match format {
+        Format::Text => {
+            println!("\n\nTop 10 optimal starting words (by average answer list reduction):");
+            for (i, ranking) in rankings.iter().take(10).enumerate() {
+                println!("{:2}. {} : {:.2}", i + 1, ranking.0, ranking.1);
+            }
+        }
+        Format::Json => {
+            let rankings: Vec<_> = rankings
+                .iter()
+                .take(10)
+                .map(|r| json!({"word": r.0, "reduction": r.1}))
+                .collect();
+            println!("{}", json!(rankings));
+        }
+    }
+}
+
+fn print_full_game_rankings(rankings: &[FullGameRanking], format: Format) {
+    match format {
+        Format::Text => {
+            println!("\n\nShortlisted starting words (by mean, maximum and failed solves):");
+            for (i, ranking) in rankings.iter().enumerate() {
+                println!(
+                    "{:2}. {} : mean {:.3}, max {}, fails {}, reduction {:.2}",
+                    i + 1,
+                    ranking.word,
+                    ranking.stats.mean,
+                    ranking.stats.max,
+                    ranking.stats.fails(),
+                    ranking.reduction
+                );
+            }
+        }
+        Format::Json => {
+            let rankings: Vec<_> = rankings
+                .iter()
+                .map(|r| {
+                    json!({
+                        "word": r.word,
+                        "reduction": r.reduction,
+                        "mean": r.stats.mean,
+                        "max": r.stats.max,
+                        "fails": r.stats.fails(),
+                    })
+                })
+                .collect();
+            println!("{}", json!(rankings));
+        }
+    }
+}
+
+/// Shows the solver finding the target word.
+pub fn demo(options: &Options, target: &str, record_path: Option<&str>) -> Result<(), HmodeError> {
+    options.hard_mode_only("demo")?;
+    let alt_words = options.alt_words;
+    let renderer = options.renderer;
+    let setter = target.parse::<Setter>()?;
+    let mut recorder = record_path
+        .map(|path| Recorder::create(path, "demo", Some(setter.word()), alt_words))
+        .transpose()?;
+    let mut solver = options.caches.solver(alt_words);
+    let mut turns = Vec::new();
+    loop {
+        let guess = solver.guess()?;
+        let result = setter.check(guess);
+        solver.filter_self(result);
+        if let Some(recorder) = &mut recorder {
+            recorder.record(guess, &result, solver.remaining())?;
+        }
+        if options.format == Format::Json {
+            turns.push(json!({
+                "guess": word_str(&guess),
+                "pattern": pattern_string(&result),
+                "remaining": solver.remaining(),
+            }));
+            if is_solved(&result) {
+                println!(
+                    "{}",
+                    json!({"target": word_str(&setter.word()), "guesses": turns})
+                );
+                break;
+            }
+            continue;
+        }
+
+        if let [
+            Clue::Right(_),
+            Clue::Right(_),
+            Clue::Right(_),
+            Clue::Right(_),
+            Clue::Right(_),
+        ] = result
+        {
+            println!("solved : {}", renderer.word(&result));
+            break;
+        }
+        println!(
+            "Guessing : {}, {}",
+            renderer.word(&result),
+            solver.remaining()
+        );
+    }
+    Ok(())
+}
+
+fn setter_for_play(seed: Option<u64>, daily: Option<&str>) -> Result<Setter, HmodeError> {
+    match (seed, daily) {
+        (Some(seed), _) => Ok(Setter::from_seed(seed)),
+        (None, Some("today")) => Ok(Setter::daily(date::today())),
+        (None, Some(d)) => Ok(Setter::daily(date::parse_date(d)?)),
+        (None, None) => Ok(Setter::new()),
+    }
+}
+
+/// Plays a game against hmode, which chooses the answer from the seed, from
+/// the date (or "today"), or else at random.
+pub fn play(
+    options: &Options,
+    seed: Option<u64>,
+    daily: Option<&str>,
+    record_path: Option<&str>,
+    tui: bool,
+) -> Result<(), HmodeError> {
+    options.text_only("play")?;
+    let alt_words = options.alt_words;
+    let setter = setter_for_play(seed, daily)?;
+    let mut recorder = record_path
+        .map(|path| Recorder::create(path, "play", Some(setter.word()), alt_words))
+        .transpose()?;
+    // The solver doesn't guess, but keeps track of the remaining answers for the log
+    let mut solver = Solver::new(alt_words);
+    if tui {
+        return run_tui(Some(setter), solver, recorder, options);
+    }
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut history = Vec::new();
+    let mut guesses = 0;
+    while guesses < MAX_GUESSES {
+        print!("Guess {}/{} : ", guesses + 1, MAX_GUESSES);
+        std::io::stdout()
+            .flush()
+            .map_err(|e| HmodeError::io("writing output", e))?;
+        let Some(line) = lines.next() else {
+            println!();
+            break;
+        };
+        let line = line.map_err(|e| HmodeError::io("reading input", e))?;
+        let guess = match to_static_word(line.trim(), false, alt_words) {
+            Ok(guess) => guess,
+            Err(e) => {
+                println!("{e}");
+                continue;
+            }
+        };
+        if let Err(e) = options.rules.check(&history, guess) {
+            println!("{e}");
+            continue;
+        }
+        guesses += 1;
+        let result = setter.check(guess);
+        history.push(result);
+        solver.record_guess(guess, result);
+        if let Some(recorder) = &mut recorder {
+            recorder.record(guess, &result, solver.remaining())?;
+        }
+        println!("            {}", options.renderer.pattern(&result));
+        if is_solved(&result) {
+            println!("Solved in {guesses} guesses");
+            return Ok(());
+        }
+    }
+    println!("The word was : {}", word_str(&setter.word()));
+    Ok(())
+}
+
+/// Suggests guesses for a game played elsewhere, from the clue patterns the
+/// user enters.
+pub fn assist(options: &Options, record_path: Option<&str>, tui: bool) -> Result<(), HmodeError> {
+    options.text_only("assist")?;
+    let alt_words = options.alt_words;
+    let mut recorder = record_path
+        .map(|path| Recorder::create(path, "assist", None, alt_words))
+        .transpose()?;
+    if tui {
+        let solver = options.caches.solver(alt_words);
+        return run_tui(None, solver, recorder, options);
+    }
+    let mut assistant = Assistant::new(alt_words, options.rules).with_caches(&options.caches);
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    println!("For each guess, enter the clue pattern using G (right place), Y (elsewhere)");
+    println!("or - (not in word), e.g. \"-GY--\". If you guessed a different word,");
+    println!("enter the word first, e.g. \"crane -GY--\".");
+    loop {
+        let suggestion = assistant.next_guess()?;
+        println!(
+            "Suggested guess : {}, {} possible answers",
+            word_str(&suggestion),
+            assistant.remaining()
+        );
+        print!("Result : ");
+        std::io::stdout()
+            .flush()
+            .map_err(|e| HmodeError::io("writing output", e))?;
+        let Some(line) = lines.next() else {
+            println!();
+            return Ok(());
+        };
+        let line = line.map_err(|e| HmodeError::io("reading input", e))?;
+        let fed = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [pattern] => assistant.feed_clue(word_str(&suggestion), pattern),
+            [word, pattern] => assistant.feed_clue(word, pattern),
+            _ => Err(HmodeError::InvalidArgument(
+                "Enter a clue pattern, optionally preceded by the word guessed".into(),
+            )),
+        };
+        let result = match fed {
+            Ok(result) => result,
+            // Clues that rule out every answer are most likely a typing
+            // mistake, so the user can enter them again
+            Err(e @ HmodeError::Contradiction { .. }) => {
+                println!("{e}. Check the clue and enter it again");
+                continue;
+            }
+            Err(e) => {
+                println!("{e}");
+                continue;
+            }
+        };
+        if options.renderer.is_coloured() {
+            println!("         {}", options.renderer.word(&result));
+        }
+        let guess = guessed_word(&result);
+        if let Some(recorder) = &mut recorder {
+            recorder.record(guess, &result, assistant.remaining())?;
+        }
+        if is_solved(&result) {
+            println!("Solved in {} guesses", assistant.history().len());
+            return Ok(());
+        }
+    }
+}
+
+/// Runs the terminal interface, in play mode if there is a setter, or else in
+/// assist mode.
+#[cfg(feature = "tui")]
+fn run_tui(
+    setter: Option<Setter>,
+    solver: Solver,
+    recorder: Option<Recorder>,
+    options: &Options,
+) -> Result<(), HmodeError> {
+    let mode = match setter {
+        Some(setter) => crate::tui::Mode::Play(setter),
+        None => crate::tui::Mode::Assist,
+    };
+    crate::tui::App::new(mode, solver, options.alt_words, options.rules)
+        .with_recorder(recorder)
+        .with_palette(options.palette)
+        .run()
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(
+    _setter: Option<Setter>,
+    _solver: Solver,
+    _recorder: Option<Recorder>,
+    _options: &Options,
+) -> Result<(), HmodeError> {
+    Err(HmodeError::InvalidArgument(
+        "hmode was built without the terminal interface. Rebuild it with --features tui".into(),
+    ))
+}
+
+/// Replays a recorded game log.
+pub fn replay(options: &Options, file: &str) -> Result<(), HmodeError> {
+    options.text_only("replay")?;
+    options.hard_mode_only("replay")?;
+    let log = GameLog::load(file)?;
+    match &log.target {
+        Some(target) => println!("Replaying {} game, target : {}", log.mode, word_str(target)),
+        None => println!("Replaying {} game", log.mode),
+    }
+    let replay = log.replay();
+    for (i, turn) in replay.turns.iter().enumerate() {
+        println!(
+            "{}. guessed {}, hmode would guess {} : {} {}",
+            i + 1,
+            word_str(&turn.guess),
+            turn.solver_guess.as_ref().map_or("-----", word_str),
+            pattern_string(&turn.result),
+            turn.remaining
+        );
+    }
+    if replay.problems.is_empty() {
+        println!("The recorded clues are consistent");
+        return Ok(());
+    }
+    for problem in &replay.problems {
+        println!("{problem}");
+    }
+    Err(HmodeError::InconsistentClues(format!(
+        "The game log contains {} inconsistencies",
+        replay.problems.len()
+    )))
+}
+
+/// Analyses a finished game, comparing each guess with the solver's choice.
+pub fn analyse(options: &Options, target: &str, guesses: &[String]) -> Result<(), HmodeError> {
+    let alt_words = options.alt_words;
+    let target = to_static_word(target, true, false)?;
+    let guesses = guesses
+        .iter()
+        .map(|g| to_static_word(g, false, alt_words))
+        .collect::<Result<Vec<_>, _>>()?;
+    let analysis = analyse::analyse_game(target, &guesses, alt_words, options.rules)?;
+    match options.format {
+        Format::Text => println!("{}", analysis.render(options.renderer)),
+        Format::Json => {
+            let mut turns = Vec::new();
+            for turn in &analysis.turns {
+                turns.push(json!({
+                    "guess": word_str(&turn.guess),
+                    "before": turn.before,
+                    "after": turn.after,
+                    "bits": turn.bits(),
+                    "best": word_str(&turn.best),
+                    "best_after": turn.best_after,
+                    "best_bits": turn.best_bits(),
+                    "rule_violation": turn.hard_mode_violation,
+                }));
+            }
+            let solved = analysis.turns.last().is_some_and(|t| t.guess == target);
+            println!(
+                "{}",
+                json!({"target": word_str(&target), "solved": solved, "turns": turns})
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Lists every recognised word.
+pub fn list_words(options: &Options) -> Result<(), HmodeError> {
+    match options.format {
+        Format::Text => {
+            for &i in hmode::all(options.alt_words) {
+                println!("{}", std::str::from_utf8(&i).expect("Invalid utf8"));
+            }
+        }
+        Format::Json => {
+            let words: Vec<_> = hmode::all(options.alt_words)
+                .iter()
+                .map(|w| std::str::from_utf8(w).expect("Invalid utf8"))
+                .collect();
+            println!("{}", json!(words));
+        }
+    }
+    Ok(())
+}
+
+/// Merges the result files from sharded runs. The files must all come from
+/// stats runs or all from rank runs.
+pub fn merge(options: &Options, files: &[String]) -> Result<(), HmodeError> {
+    let (merged, shard_problem) = hmode::merge(files)?;
+    match merged {
+        Merged::Stats(stats) => print_stats(&stats, options.format),
+        Merged::Rankings(rankings) => print_rankings(&rankings, options.format),
+    }
+    if let Some(problem) = shard_problem {
+        eprintln!("Warning : {problem}");
+    }
+    Ok(())
+}
+
+/// Calculates the second guess for every clue pattern of the start word, and
+/// saves the table in the cache directory and optionally to another file.
+pub fn second_guesses(
+    options: &Options,
+    start: Option<&str>,
+    output: Option<&str>,
+) -> Result<(), HmodeError> {
+    options.hard_mode_only("second-guesses")?;
+    let start = start.map_or_else(
+        || String::from_utf8_lossy(&hmode::DEFAULT_START_WORD).into_owned(),
+        str::to_string,
+    );
+    install_interrupt_handler()?;
+    let table = SecondGuessTable::build(&start, options.alt_words)?;
+    let default_path = table.save_default()?;
+    eprintln!("Saved the second guess table to {}", default_path.display());
+    if let Some(path) = output {
+        table.save(path)?;
+    }
+    print_second_guesses(&table, options.format);
+    Ok(())
+}
+
+fn print_second_guesses(table: &SecondGuessTable, format: Format) {
+    let rows = table
+        .patterns()
+        .map(|(pattern, entry)| (pattern_string(&pattern), entry));
+    match format {
+        Format::Text => {
+            println!(
+                "Second guesses for start word {}",
+                word_str(&table.start_word)
+            );
+            println!("Pattern  Remaining  Guess");
+            for (pattern, entry) in rows {
+                match entry {
+                    Some(entry) => println!(
+                        "{pattern}    {:9}  {}",
+                        entry.remaining,
+                        word_str(&entry.guess)
+                    ),
+                    None => println!("{pattern}    {:9}  -", 0),
+                }
+            }
+        }
+        Format::Json => {
+            let mut patterns = Vec::new();
+            for (pattern, entry) in rows {
+                patterns.push(json!({
+                    "pattern": pattern,
+                    "remaining": entry.map_or(0, |e| e.remaining),
+                    "guess": entry.map(|e| word_str(&e.guess).to_string()),
+                }));
+            }
+            println!(
+                "{}",
+                json!({"start_word": word_str(&table.start_word), "patterns": patterns})
+            );
+        }
+    }
+}
+
+/// Serves the JSON API on the given port.
+#[cfg(feature = "serve")]
+pub fn serve(options: &Options, port: u16) -> Result<(), HmodeError> {
+    crate::server::Api::new(options.alt_words, options.rules, options.caches.clone()).serve(port)
+}
+
+#[cfg(not(feature = "serve"))]
+pub fn serve(_options: &Options, _port: u16) -> Result<(), HmodeError> {
+    Err(HmodeError::InvalidArgument(
+        "hmode was built without the server. Rebuild it with --features serve".into(),
+    ))
+}
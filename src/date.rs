@@ -0,0 +1,85 @@
+use hmode::HmodeError;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Returns the number of days between 1970-01-01 and the given date in the
+/// proleptic Gregorian calendar. Dates before 1970 give negative values.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // Shift the year so that it starts in March, which puts the leap day at
+    // the end of the year and makes the day-of-year calculation regular.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses a date in the form YYYY-MM-DD, and returns the number of days
+/// since 1970-01-01.
+pub fn parse_date(date: &str) -> Result<i64, HmodeError> {
+    let invalid = || {
+        HmodeError::InvalidArgument(format!(
+            "Invalid date '{date}'. Dates must be in the form YYYY-MM-DD"
+        ))
+    };
+    let mut parts = date.trim().splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let year: i64 = year.parse().map_err(|_| invalid())?;
+    let month: u32 = month.parse().map_err(|_| invalid())?;
+    let day: u32 = day.parse().map_err(|_| invalid())?;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(invalid());
+    }
+    Ok(days_from_civil(year, month, day))
+}
+
+/// Returns the number of days since 1970-01-01 for the current date in UTC.
+pub fn today() -> i64 {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    (seconds / 86_400) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_date_epoch() {
+        assert_eq!(parse_date("1970-01-01"), Ok(0));
+    }
+
+    #[test]
+    fn parse_date_handles_leap_years() {
+        let days = |a, b| parse_date(a).unwrap() - parse_date(b).unwrap();
+        assert_eq!(days("2024-03-01", "2024-02-28"), 2);
+        assert_eq!(days("2023-03-01", "2023-02-28"), 1);
+        assert_eq!(parse_date("2000-02-29"), Ok(11_016));
+    }
+
+    #[test]
+    fn parse_date_rejects_invalid_dates() {
+        assert!(parse_date("2023-02-29").is_err());
+        assert!(parse_date("2023-13-01").is_err());
+        assert!(parse_date("2023-01").is_err());
+        assert!(parse_date("yesterday").is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::error::HmodeError;
+use crate::line_file;
+use crate::setter::{self, CheckResult};
+use crate::solver::STRATEGY;
+use crate::words::{WdlWord, to_static_word, word_str};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// A decision cache is a line file with a line for each decision, in the form
+//   decision <start word> [<guess> <pattern>]... <next guess>
+const CACHE_HEADER: &str = "# hmode decision cache";
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+struct DecisionKey {
+    start_word: WdlWord,
+    history: Vec<CheckResult>,
+}
+
+/// Remembers the solver's guess for each game position, so that positions
+/// reached again, e.g. the same early guesses for many answers, don't have to
+/// be searched again. The solver's choice depends only on the clues received
+/// so far, for a given word list and strategy. The solver always plays by the
+/// hard mode rules, so the rules of the game don't matter.
+#[derive(Debug)]
+pub struct DecisionCache {
+    alt_words: bool,
+    decisions: Mutex<HashMap<DecisionKey, WdlWord>>,
+    // Whether there are decisions that haven't been saved
+    changed: AtomicBool,
+}
+
+impl DecisionCache {
+    pub fn new(alt_words: bool) -> Self {
+        DecisionCache {
+            alt_words,
+            decisions: Mutex::new(HashMap::new()),
+            changed: AtomicBool::new(false),
+        }
+    }
+
+    /// Loads the decisions saved by an earlier run, or starts an empty cache
+    /// if the file doesn't exist yet. The cache must have been written using
+    /// the same word list and strategy.
+    pub fn load_or_new(path: &str, alt_words: bool) -> Result<Self, HmodeError> {
+        let cache = DecisionCache::new(alt_words);
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(cache),
+            Err(e) => return Err(HmodeError::io(format!("reading file {path}"), e)),
+        };
+        cache
+            .parse(&text)
+            .map_err(|e| HmodeError::invalid_file(path, e))?;
+        Ok(cache)
+    }
+
+    pub fn alt_words(&self) -> bool {
+        self.alt_words
+    }
+
+    pub fn len(&self) -> usize {
+        self.decisions.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether decisions have been added since the cache was loaded or saved.
+    pub fn is_changed(&self) -> bool {
+        self.changed.load(Ordering::Relaxed)
+    }
+
+    /// Returns the guess made earlier in the position reached by the given clues.
+    pub fn get(&self, start_word: WdlWord, history: &[CheckResult]) -> Option<WdlWord> {
+        let key = DecisionKey {
+            start_word,
+            history: history.to_vec(),
+        };
+        self.decisions.lock().unwrap().get(&key).copied()
+    }
+
+    pub fn insert(&self, start_word: WdlWord, history: &[CheckResult], guess: WdlWord) {
+        let key = DecisionKey {
+            start_word,
+            history: history.to_vec(),
+        };
+        if self.decisions.lock().unwrap().insert(key, guess) != Some(guess) {
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Saves the decisions, replacing the previous file.
+    pub fn save(&self, path: &str) -> Result<(), HmodeError> {
+        let mut lines: Vec<String> = self
+            .decisions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, guess)| {
+                let mut line = format!("decision {}", word_str(&key.start_word));
+                for result in &key.history {
+                    line.push_str(&format!(
+                        " {} {}",
+                        word_str(&setter::guessed_word(result)),
+                        setter::pattern_string(result)
+                    ));
+                }
+                line.push_str(&format!(" {}\n", word_str(guess)));
+                line
+            })
+            .collect();
+        lines.sort();
+        let text = format!(
+            "{CACHE_HEADER}\nstrategy {STRATEGY}\nalt-words {}\n{}",
+            self.alt_words,
+            lines.concat()
+        );
+        line_file::save(path, &text)?;
+        self.changed.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub(crate) fn parse(&self, text: &str) -> Result<(), String> {
+        let mut decisions = self.decisions.lock().unwrap();
+        for line in line_file::lines(text) {
+            let mismatch = |setting: &str| {
+                line.error(format!("the cache was written with a different {setting}"))
+            };
+            let to_word =
+                |w: &str| to_static_word(w, false, self.alt_words).map_err(|e| line.error(e));
+            match line.fields.as_slice() {
+                ["strategy", strategy] if *strategy != STRATEGY => {
+                    return Err(mismatch("strategy"));
+                }
+                ["alt-words", alt] if alt.parse() != Ok(self.alt_words) => {
+                    return Err(mismatch("word list"));
+                }
+                // Older caches recorded the rules, which don't affect the solver
+                ["strategy" | "alt-words" | "rules", _] => {}
+                ["decision", start_word, moves @ .., guess] if moves.len() % 2 == 0 => {
+                    let history = moves
+                        .chunks(2)
+                        .map(|m| {
+                            setter::parse_pattern(to_word(m[0])?, m[1]).map_err(|e| line.error(e))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let key = DecisionKey {
+                        start_word: to_word(start_word)?,
+                        history,
+                    };
+                    decisions.insert(key, to_word(guess)?);
+                }
+                _ => return Err(line.unrecognised()),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setter::Setter;
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("hmode-decisions-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let history = [
+            Setter::from_word(*b"crook").check(*b"tares"),
+            Setter::from_word(*b"crook").check(*b"prion"),
+        ];
+        let cache = DecisionCache::new(false);
+        assert!(!cache.is_changed());
+        cache.insert(*b"tares", &history[..1], *b"prion");
+        cache.insert(*b"tares", &history, *b"crook");
+        assert!(cache.is_changed());
+        cache.save(path).unwrap();
+        assert!(!cache.is_changed());
+        // Making the same decision again doesn't need a save
+        cache.insert(*b"tares", &history, *b"crook");
+        assert!(!cache.is_changed());
+
+        let loaded = DecisionCache::load_or_new(path, false).unwrap();
+        assert!(!loaded.is_changed());
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get(*b"tares", &history), Some(*b"crook"));
+        assert_eq!(loaded.get(*b"crane", &history), None);
+        assert!(DecisionCache::load_or_new(path, true).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn missing_file_gives_empty_cache() {
+        let cache = DecisionCache::load_or_new("/nonexistent/hmode-cache", false);
+        assert_eq!(cache.map(|c| c.len()), Ok(0));
+    }
+}
@@ -0,0 +1,214 @@
+/// The errors returned by the hmode library.
+#[derive(Debug)]
+pub enum HmodeError {
+    /// A word that doesn't have 5 letters
+    WrongLength(String),
+    /// A word containing characters other than the letters a to z
+    NonAlphabetic(String),
+    /// A word that isn't in the word list, which was the alternative list if
+    /// `alt_words` is set. The nearest valid words are only looked for when
+    /// the error is displayed
+    NotInList { word: String, alt_words: bool },
+    /// A valid guess that was given where an answer word is needed
+    NotAnAnswer(String),
+    /// A clue pattern that isn't 5 of 'G', 'Y' or '-'
+    InvalidPattern(String),
+    /// A guess that breaks the hard mode rules, with a description of the rule
+    HardModeViolation(String),
+    /// Clues that no answer word could have given
+    InconsistentClues(String),
+    /// A guess and clue pattern that left no answer word matching all the clues
+    Contradiction { guess: String, pattern: String },
+    /// A file that isn't in the expected format, or doesn't match the run
+    InvalidFile { path: String, message: String },
+    /// A command line argument or option that can't be used
+    InvalidArgument(String),
+    /// A long run that was stopped before it finished, whose results are missing
+    /// or partial
+    Interrupted(String),
+    /// An error reading or writing a file or the terminal
+    Io {
+        context: String,
+        source: std::io::Error,
+    },
+}
+
+impl HmodeError {
+    pub fn invalid_file(path: &str, message: impl Into<String>) -> Self {
+        HmodeError::InvalidFile {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// The valid words nearest to a word that isn't in the list, closest
+    /// first, or none for other errors.
+    pub fn suggestions(&self) -> Vec<String> {
+        match self {
+            HmodeError::NotInList { word, alt_words } => {
+                crate::words::suggestions(word, crate::words::all(*alt_words))
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Describes an I/O error, given what was being done, e.g. "reading file x".
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        HmodeError::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// The exit code used by the command line tool for each kind of error, so
+    /// that scripts can tell them apart. 2 is also used for usage errors found
+    /// while parsing the command line.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            HmodeError::InvalidArgument(_) => 2,
+            HmodeError::WrongLength(_) => 3,
+            HmodeError::NonAlphabetic(_) => 4,
+            HmodeError::NotInList { .. } => 5,
+            HmodeError::NotAnAnswer(_) => 6,
+            HmodeError::InvalidPattern(_) => 7,
+            HmodeError::HardModeViolation(_) => 8,
+            HmodeError::InconsistentClues(_) => 9,
+            HmodeError::InvalidFile { .. } => 10,
+            HmodeError::Io { .. } => 11,
+            HmodeError::Contradiction { .. } => 12,
+            HmodeError::Interrupted(_) => 130,
+        }
+    }
+}
+
+impl std::fmt::Display for HmodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HmodeError::WrongLength(word) => write!(f, "The word '{word}' must have 5 letters"),
+            HmodeError::NonAlphabetic(word) => {
+                write!(f, "The word '{word}' must only contain the letters a to z")
+            }
+            HmodeError::NotInList { word, .. } => {
+                write!(f, "The word '{word}' is not in the list of valid words")?;
+                let suggestions = self.suggestions();
+                if !suggestions.is_empty() {
+                    write!(f, ". Did you mean {}?", suggestions.join(", "))?;
+                }
+                Ok(())
+            }
+            HmodeError::NotAnAnswer(word) => {
+                write!(
+                    f,
+                    "The word '{word}' is a valid guess, but can't be an answer"
+                )
+            }
+            HmodeError::InvalidPattern(pattern) => write!(
+                f,
+                "Invalid clue pattern '{pattern}'. Use 5 of G (right place), Y (elsewhere) or - (not in word)"
+            ),
+            HmodeError::HardModeViolation(rule) => write!(f, "Hard mode rule broken : {rule}"),
+            HmodeError::Contradiction { guess, pattern } => write!(
+                f,
+                "No answer word matches the clue {pattern} for {guess} along with the earlier clues"
+            ),
+            HmodeError::InconsistentClues(message)
+            | HmodeError::InvalidArgument(message)
+            | HmodeError::Interrupted(message) => write!(f, "{message}"),
+            HmodeError::InvalidFile { path, message } => {
+                write!(f, "Error in file {path}, {message}")
+            }
+            HmodeError::Io { context, source } => write!(f, "Error {context}, {source}"),
+        }
+    }
+}
+
+impl std::error::Error for HmodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HmodeError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// I/O errors are equal if they are the same kind of error in the same context
+impl PartialEq for HmodeError {
+    fn eq(&self, other: &Self) -> bool {
+        use HmodeError::*;
+        match (self, other) {
+            (WrongLength(a), WrongLength(b))
+            | (NonAlphabetic(a), NonAlphabetic(b))
+            | (NotAnAnswer(a), NotAnAnswer(b))
+            | (InvalidPattern(a), InvalidPattern(b))
+            | (HardModeViolation(a), HardModeViolation(b))
+            | (InconsistentClues(a), InconsistentClues(b))
+            | (InvalidArgument(a), InvalidArgument(b))
+            | (Interrupted(a), Interrupted(b)) => a == b,
+            (
+                NotInList { word, alt_words },
+                NotInList {
+                    word: other_word,
+                    alt_words: other_alt_words,
+                },
+            ) => word == other_word && alt_words == other_alt_words,
+            (
+                Contradiction { guess, pattern },
+                Contradiction {
+                    guess: other_guess,
+                    pattern: other_pattern,
+                },
+            ) => guess == other_guess && pattern == other_pattern,
+            (
+                InvalidFile { path, message },
+                InvalidFile {
+                    path: other_path,
+                    message: other_message,
+                },
+            ) => path == other_path && message == other_message,
+            (
+                Io { context, source },
+                Io {
+                    context: other_context,
+                    source: other_source,
+                },
+            ) => context == other_context && source.kind() == other_source.kind(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let io = || std::io::Error::from(std::io::ErrorKind::NotFound);
+        let errors = [
+            HmodeError::WrongLength("".into()),
+            HmodeError::NonAlphabetic("".into()),
+            HmodeError::NotInList {
+                word: "".into(),
+                alt_words: false,
+            },
+            HmodeError::NotAnAnswer("".into()),
+            HmodeError::InvalidPattern("".into()),
+            HmodeError::HardModeViolation("".into()),
+            HmodeError::InconsistentClues("".into()),
+            HmodeError::Contradiction {
+                guess: "".into(),
+                pattern: "".into(),
+            },
+            HmodeError::invalid_file("", ""),
+            HmodeError::InvalidArgument("".into()),
+            HmodeError::Interrupted("".into()),
+            HmodeError::io("", io()),
+        ];
+        let mut codes: Vec<u8> = errors.iter().map(HmodeError::exit_code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0) && !codes.contains(&1));
+        assert_eq!(HmodeError::io("x", io()), HmodeError::io("x", io()));
+    }
+}
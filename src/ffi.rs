@@ -0,0 +1,208 @@
+//! A C ABI for the solver, declared in include/hmode.h. Functions that can
+//! fail return 0 on success, or the exit code of the error otherwise (see the
+//! README), and the error's message can be read with hmode_solver_last_error.
+//! A NULL solver or buffer gives the exit code of an invalid argument.
+
+use crate::assistant::Assistant;
+use crate::error::HmodeError;
+use crate::setter::RuleSet;
+use std::ffi::{CStr, CString, c_char, c_int};
+
+/// A solver for a game played elsewhere, opaque to C.
+pub struct HmodeSolver {
+    assistant: Assistant,
+    last_error: CString,
+}
+
+impl HmodeSolver {
+    fn result(&mut self, result: Result<(), HmodeError>) -> c_int {
+        match result {
+            Ok(()) => {
+                self.last_error = CString::default();
+                0
+            }
+            Err(e) => {
+                // Messages don't contain NULs, but an empty one is better than none
+                self.last_error = CString::new(e.to_string()).unwrap_or_default();
+                e.exit_code() as c_int
+            }
+        }
+    }
+}
+
+fn null_solver() -> c_int {
+    HmodeError::InvalidArgument(String::new()).exit_code() as c_int
+}
+
+unsafe fn to_str<'a>(s: *const c_char, name: &str) -> Result<&'a str, HmodeError> {
+    if s.is_null() {
+        return Err(HmodeError::InvalidArgument(format!("The {name} is NULL")));
+    }
+    // SAFETY: the caller passes a NUL terminated string
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|_| HmodeError::InvalidArgument(format!("The {name} isn't valid UTF-8")))
+}
+
+/// Creates a solver for a new game, using the alternative word lists if
+/// `alt_words` is set and enforcing the hard mode rules if `hard_mode` is.
+/// The solver must be released with hmode_solver_free.
+#[unsafe(no_mangle)]
+pub extern "C" fn hmode_solver_new(alt_words: bool, hard_mode: bool) -> *mut HmodeSolver {
+    let rules = if hard_mode {
+        RuleSet::Hard
+    } else {
+        RuleSet::Normal
+    };
+    Box::into_raw(Box::new(HmodeSolver {
+        assistant: Assistant::new(alt_words, rules),
+        last_error: CString::default(),
+    }))
+}
+
+/// Records a guess and its clue pattern of G, Y and -, e.g. "--Y--". A clue
+/// that breaks the rules or leaves no possible answer is rejected, and the
+/// solver is left as it was.
+///
+/// # Safety
+/// `solver` must come from hmode_solver_new, and `guess` and `pattern` must be
+/// NUL terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hmode_solver_submit(
+    solver: *mut HmodeSolver,
+    guess: *const c_char,
+    pattern: *const c_char,
+) -> c_int {
+    // SAFETY: the caller passes a live solver or NULL
+    let Some(solver) = (unsafe { solver.as_mut() }) else {
+        return null_solver();
+    };
+    // SAFETY: the caller passes NUL terminated strings
+    let result = unsafe { submit(&mut solver.assistant, guess, pattern) };
+    solver.result(result)
+}
+
+unsafe fn submit(
+    assistant: &mut Assistant,
+    guess: *const c_char,
+    pattern: *const c_char,
+) -> Result<(), HmodeError> {
+    // SAFETY: the caller passes NUL terminated strings
+    let (guess, pattern) = unsafe { (to_str(guess, "guess")?, to_str(pattern, "pattern")?) };
+    assistant.feed_clue(guess, pattern).map(|_| ())
+}
+
+/// Writes the guess the solver would make next to `out`, as 5 letters and a
+/// NUL.
+///
+/// # Safety
+/// `solver` must come from hmode_solver_new, and `out` must have room for 6
+/// bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hmode_solver_next_guess(
+    solver: *mut HmodeSolver,
+    out: *mut c_char,
+) -> c_int {
+    // SAFETY: the caller passes a live solver or NULL
+    let Some(solver) = (unsafe { solver.as_mut() }) else {
+        return null_solver();
+    };
+    if out.is_null() {
+        let e = HmodeError::InvalidArgument("The output buffer is NULL".into());
+        return solver.result(Err(e));
+    }
+    let result = solver.assistant.next_guess().map(|guess| {
+        let mut word = [0; 6];
+        word[..5].copy_from_slice(&guess);
+        // SAFETY: the caller passes a buffer of at least 6 bytes
+        unsafe { std::ptr::copy_nonoverlapping(word.as_ptr().cast(), out, word.len()) };
+    });
+    solver.result(result)
+}
+
+/// The number of answer words that match the clues so far, or 0 for a NULL
+/// solver.
+///
+/// # Safety
+/// `solver` must come from hmode_solver_new.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hmode_solver_remaining(solver: *const HmodeSolver) -> usize {
+    // SAFETY: the caller passes a live solver or NULL
+    unsafe { solver.as_ref() }.map_or(0, |solver| solver.assistant.remaining())
+}
+
+/// The message of the error returned by the last call with the solver that
+/// can fail, or an empty string if it succeeded. It is valid until the next call with the solver. A NULL solver gives an
+/// empty string.
+///
+/// # Safety
+/// `solver` must come from hmode_solver_new.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hmode_solver_last_error(solver: *const HmodeSolver) -> *const c_char {
+    // SAFETY: the caller passes a live solver or NULL
+    unsafe { solver.as_ref() }.map_or(c"".as_ptr(), |solver| solver.last_error.as_ptr())
+}
+
+/// Releases a solver. NULL is ignored.
+///
+/// # Safety
+/// `solver` must come from hmode_solver_new, and not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hmode_solver_free(solver: *mut HmodeSolver) {
+    if !solver.is_null() {
+        // SAFETY: the solver was allocated by hmode_solver_new
+        drop(unsafe { Box::from_raw(solver) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_through_the_c_abi() {
+        let mut guess = [0 as c_char; 6];
+        unsafe {
+            let solver = hmode_solver_new(false, true);
+            assert_eq!(hmode_solver_next_guess(solver, guess.as_mut_ptr()), 0);
+            assert_eq!(CStr::from_ptr(guess.as_ptr()), c"tares");
+            assert_eq!(
+                hmode_solver_submit(solver, guess.as_ptr(), c"--Y--".as_ptr()),
+                0
+            );
+            let remaining = hmode_solver_remaining(solver);
+            assert!(remaining < 100);
+
+            // Rejected clues give the error's exit code and message
+            assert_eq!(
+                hmode_solver_submit(solver, c"tares".as_ptr(), c"GGGGX".as_ptr()),
+                7
+            );
+            assert_eq!(
+                hmode_solver_submit(solver, c"pious".as_ptr(), c"-----".as_ptr()),
+                8
+            );
+            assert!(!CStr::from_ptr(hmode_solver_last_error(solver)).is_empty());
+            assert_eq!(hmode_solver_next_guess(solver, guess.as_mut_ptr()), 0);
+            assert!(CStr::from_ptr(hmode_solver_last_error(solver)).is_empty());
+            assert_eq!(
+                hmode_solver_submit(solver, std::ptr::null(), c"-----".as_ptr()),
+                2
+            );
+            assert_eq!(hmode_solver_next_guess(solver, std::ptr::null_mut()), 2);
+            assert_eq!(hmode_solver_remaining(solver), remaining);
+            hmode_solver_free(solver);
+
+            // A NULL solver is an invalid argument rather than a crash
+            let null = std::ptr::null_mut();
+            assert_eq!(
+                hmode_solver_submit(null, c"tares".as_ptr(), c"-----".as_ptr()),
+                2
+            );
+            assert_eq!(hmode_solver_next_guess(null, guess.as_mut_ptr()), 2);
+            assert_eq!(hmode_solver_remaining(null), 0);
+            assert!(CStr::from_ptr(hmode_solver_last_error(null)).is_empty());
+            hmode_solver_free(null);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Entry points for the fuzz targets in fuzz/ into the parsers of hmode's
+//! file formats, and the clue patterns and word filter that the filter target
+//! plays with. Only built with `--cfg fuzzing`, as `cargo fuzz` does.
+
+use crate::decision_cache::DecisionCache;
+use crate::record::GameLog;
+use crate::results::StatsResults;
+use crate::second_guess::SecondGuessTable;
+use crate::setter::CheckResult;
+use crate::solver::Solver;
+use crate::words::WdlWord;
+
+pub use crate::setter::{PATTERN_COUNT, pattern_from_index};
+
+pub fn parse_game_log(text: &str) -> Result<(), String> {
+    GameLog::parse(text).map(|_| ())
+}
+
+pub fn parse_checkpoint(text: &str) -> Result<(), String> {
+    crate::checkpoint::parse(text).map(|_| ())
+}
+
+pub fn parse_second_guesses(text: &str) -> Result<(), String> {
+    SecondGuessTable::parse(text).map(|_| ())
+}
+
+pub fn parse_results(text: &str) -> Result<(), String> {
+    StatsResults::parse(text).map(|_| ())
+}
+
+pub fn parse_decisions(text: &str) -> Result<(), String> {
+    DecisionCache::new(false).parse(text)
+}
+
+pub fn count_matching(list: &[WdlWord], clues: CheckResult) -> usize {
+    Solver::count_matching(list, clues)
+}
@@ -0,0 +1,71 @@
+//! Sets and solves Wordle puzzles in hard mode.
+//!
+//! A [`Setter`] knows the answer word and gives the [`Clue`]s for each guess,
+//! and a [`Solver`] uses the clues to choose its guesses until it finds the
+//! answer. The items exported from the crate root are the API, which the
+//! hmode command line tool is built on.
+//!
+//! ```
+//! use hmode::{Setter, Solver, is_solved};
+//!
+//! let setter = "crook".parse::<Setter>()?;
+//! let mut solver = Solver::new(false);
+//! loop {
+//!     let guess = solver.guess()?;
+//!     let clues = setter.check(guess);
+//!     if is_solved(&clues) {
+//!         assert_eq!(&guess, b"crook");
+//!         break;
+//!     }
+//!     solver.filter_self(clues);
+//! }
+//! # Ok::<(), hmode::HmodeError>(())
+//! ```
+
+mod assistant;
+mod checkpoint;
+mod decision_cache;
+mod error;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub mod fuzzing;
+mod line_file;
+mod merge;
+mod progress;
+#[cfg(feature = "python")]
+mod python;
+mod record;
+mod results;
+mod second_guess;
+mod setter;
+mod shard;
+mod solver;
+mod stats;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod words;
+
+pub use assistant::Assistant;
+pub use checkpoint::{Checkpoint, StartWordRanking};
+pub use decision_cache::DecisionCache;
+pub use error::HmodeError;
+pub use merge::{Merged, merge};
+pub use progress::{cancel, cancelled, reset_cancelled};
+pub use record::{GameLog, Recorder, Replay, ReplayedTurn, Turn};
+pub use results::StatsResults;
+pub use second_guess::{SecondGuess, SecondGuessTable};
+pub use setter::{
+    CheckResult, Clue, MAX_GUESSES, RuleSet, Setter, guessed_word, is_solved, parse_pattern,
+    pattern_string,
+};
+pub use shard::Shard;
+pub use solver::Solver;
+pub use stats::{
+    FullGameRanking, Outlier, Solve, SolverCaches, Stats, find_optimal_start_word, rank_full_games,
+    solve_answers,
+};
+pub use words::{
+    DEFAULT_START_WORD, WdlWord, WordIndex, all, answers, normalise, to_static_word, word_str,
+};
@@ -0,0 +1,84 @@
+use crate::error::HmodeError;
+use std::fmt::Display;
+use std::fs;
+
+// The files hmode writes, such as checkpoints and game logs, are line-oriented
+// text. Each line is a keyword followed by its values, separated by spaces.
+// Blank lines and lines starting with '#' are ignored, and each kind of file
+// starts with a comment naming it.
+
+/// A line of a file, split into its keyword and values.
+pub(crate) struct Line<'a> {
+    /// The line number, counting from 1
+    pub number: usize,
+    pub fields: Vec<&'a str>,
+}
+
+impl Line<'_> {
+    /// The message for an error in this line.
+    pub fn error(&self, message: impl Display) -> String {
+        bad_line(self.number, message)
+    }
+
+    /// The message for a line that isn't a valid entry.
+    pub fn unrecognised(&self) -> String {
+        self.error(format!("unrecognised entry '{}'", self.fields.join(" ")))
+    }
+}
+
+/// The message for an error in a line, for errors found once the whole file
+/// has been read.
+pub(crate) fn bad_line(number: usize, message: impl Display) -> String {
+    format!("line {number} : {message}")
+}
+
+/// Returns the entries in the text of a file, skipping blank lines and
+/// comments.
+pub(crate) fn lines(text: &str) -> impl Iterator<Item = Line<'_>> {
+    text.lines()
+        .enumerate()
+        .map(|(n, line)| (n, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(n, line)| Line {
+            number: n + 1,
+            fields: line.split_whitespace().collect(),
+        })
+}
+
+/// Reads a file and parses its text, reporting errors against the path.
+pub(crate) fn load<T>(
+    path: &str,
+    parse: impl FnOnce(&str) -> Result<T, String>,
+) -> Result<T, HmodeError> {
+    let text =
+        fs::read_to_string(path).map_err(|e| HmodeError::io(format!("reading file {path}"), e))?;
+    parse(&text).map_err(|e| HmodeError::invalid_file(path, e))
+}
+
+/// Saves the text of a file, replacing the previous file. The text is written
+/// to a temporary file first so that an interruption while saving doesn't lose
+/// the previous file.
+pub(crate) fn save(path: &str, text: &str) -> Result<(), HmodeError> {
+    let temp_path = format!("{path}.tmp");
+    fs::write(&temp_path, text)
+        .and_then(|_| fs::rename(&temp_path, path))
+        .map_err(|e| HmodeError::io(format!("writing file {path}"), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_skip_blank_lines_and_comments() {
+        let text = "# header\n\n  alt-words true \n#note\nscore tares 2203.1\n";
+        let lines: Vec<_> = lines(text).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].number, 3);
+        assert_eq!(lines[0].fields, ["alt-words", "true"]);
+        assert_eq!(
+            lines[1].unrecognised(),
+            "line 5 : unrecognised entry 'score tares 2203.1'"
+        );
+    }
+}
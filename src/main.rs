@@ -1,227 +1,211 @@
-mod setter;
-mod solver;
-mod words;
-
-use clap::Parser;
-use setter::Clue;
-use std::io::Write;
+mod analyse;
+mod colour;
+mod commands;
+mod date;
+#[cfg(feature = "serve")]
+mod server;
+#[cfg(feature = "tui")]
+mod tui;
+
+use clap::{Parser, Subcommand};
+use colour::Palette;
+use commands::{Format, Options};
+use hmode::{HmodeError, RuleSet, Shard};
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    #[arg(short, long)]
+    #[command(subcommand)]
+    command: Command,
+    /// Uses a shorter alternative word list, instead of the very obscure Wordle list of valid words
+    #[arg(short, long, global = true)]
+    alt_words: bool,
+    /// The rules that guesses must follow, either "hard" or "normal". The
+    /// solver always plays by the hard mode rules, so the commands where it
+    /// plays, demo, stats, rank, replay and second-guesses, reject normal
+    #[arg(long, global = true, default_value_t = RuleSet::Hard)]
+    rules: RuleSet,
+    /// The output format for the stats, rank, demo, words and analyse commands
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Looks up the solver's second guess in a table written by the
+    /// second-guesses command, instead of the table it saved in the cache
+    /// directory. The table is only used for games with the start word it was
+    /// built for
+    #[arg(long, global = true)]
+    second_guess_cache: Option<String>,
+    /// Loads the solver's earlier decisions from the given file, if it exists,
+    /// and saves them back at the end of the run, so that repeated stats runs
+    /// and assist sessions reuse earlier work
+    #[arg(long, global = true)]
+    decision_cache: Option<String>,
+    /// The clue colours, either "standard" (green and yellow) or
+    /// "high-contrast" (orange and blue). Colour is only used on a terminal,
+    /// and not if the NO_COLOR environment variable is set
+    #[arg(long, global = true, default_value_t = Palette::Standard)]
+    palette: Palette,
+}
+
+#[derive(Subcommand)]
+enum Command {
     /// Calculates the average number of solving steps
     /// and list words that take more than 6 steps to solve,
     /// given a specific starting word. This may take several minutes
     /// to run on typical desktop hardware.
-    start_word: Option<String>,
+    Stats {
+        start_word: String,
+        /// Solves only one slice of the answer list, in the form i/n, so that
+        /// the work can be split across processes. The output file holds the
+        /// shard's results, so it must be given
+        #[arg(long, requires = "output")]
+        shard: Option<Shard>,
+        /// Writes the number of guesses for each answer to the given file,
+        /// which can be merged with the results of other shards
+        #[arg(long)]
+        output: Option<String>,
+    },
     /// Identifies the optimal starting words for Wordle by calculating
     /// the average reduction in the answer list size. This may take several minutes
     /// to run on typical desktop hardware.
-    #[arg(long)]
-    find_optimal_start: bool,
+    Rank {
+        /// Periodically saves the score for each word to the given file, which
+        /// must not exist unless the run is resumed
+        #[arg(long)]
+        checkpoint: Option<String>,
+        /// Resumes an interrupted run from its checkpoint file, if it has one,
+        /// skipping the words that have already been scored
+        #[arg(long, requires = "checkpoint")]
+        resume: bool,
+        /// Scores only one slice of the word list, in the form i/n, so that the
+        /// work can be split across processes. The checkpoint file holds the
+        /// shard's results, so it must be given
+        #[arg(long, conflicts_with = "shortlist", requires = "checkpoint")]
+        shard: Option<Shard>,
+        /// Takes the given number of best words from the first-step ranking,
+        /// and ranks them again by the statistics of full games over the whole
+        /// answer list. This takes a few minutes for each shortlisted word
+        #[arg(long)]
+        shortlist: Option<usize>,
+    },
+    /// Combines the result files written by sharded stats or rank runs into
+    /// the final results
+    Merge {
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
     /// Performs a demo of the solver, where the provided word is the solution
-    #[arg(short, long)]
-    demo: Option<String>,
+    Demo {
+        target: String,
+        /// Records the session to the given file
+        #[arg(long)]
+        record: Option<String>,
+    },
+    /// Plays an interactive game, where hmode chooses a secret word and
+    /// the user tries to guess it
+    Play {
+        /// Seeds the random choice of secret word, so that a game can be replayed
+        #[arg(long, conflicts_with = "daily")]
+        seed: Option<u64>,
+        /// Plays the puzzle for the given date (YYYY-MM-DD), or for today if no
+        /// date is given. Everyone playing on the same date gets the same word
+        #[arg(long, num_args = 0..=1, default_missing_value = "today")]
+        daily: Option<String>,
+        /// Records the session to the given file
+        #[arg(long)]
+        record: Option<String>,
+        /// Plays in a full screen terminal interface, with an on-screen keyboard
+        #[arg(long)]
+        tui: bool,
+    },
+    /// Suggests guesses for a game being played elsewhere, e.g. the official
+    /// Wordle game. Enter the clue pattern received for each guess
+    Assist {
+        /// Records the session to the given file
+        #[arg(long)]
+        record: Option<String>,
+        /// Assists in a full screen terminal interface, with an on-screen keyboard
+        #[arg(long)]
+        tui: bool,
+    },
+    /// Replays a recorded session, checking that the recorded clues are
+    /// consistent and showing what hmode would have guessed at each step
+    Replay { file: String },
+    /// Calculates the solver's second guess for every clue pattern that the
+    /// start word can receive, and saves the table in the cache directory,
+    /// where the other commands find it for games with that start word. This
+    /// may take a few minutes
+    SecondGuesses {
+        /// The start word, "tares" by default
+        #[arg(long)]
+        start: Option<String>,
+        /// Writes the table to the given file
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Serves games, solver suggestions and stats as a JSON API over HTTP on
+    /// localhost, for web front ends and bots
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+    },
     /// Prints the complete list of recognised Words
-    #[arg(long)]
-    list_words: bool,
-    /// Uses a shorter alternative word list, instead of the very obscure Wordle list of valid words
-    #[arg(short, long)]
-    alt_words: bool,
-}
-
-struct Outlier(String, u32);
-struct Stats(f32, Vec<Outlier>);
-
-impl std::fmt::Display for Stats {
-    // This trait requires `fmt` with this exact signature.
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        // Write strictly the first element into the supplied output
-        // stream: `f`. Returns `fmt::Result` which indicates whether the
-        // operation succeeded or failed. Note that `write!` uses syntax which
-        // is very similar to `println!`.
-        write!(f, "\nAverage solving steps : {}\n", self.0)?;
-        if !self.1.is_empty() {
-            write!(f, "\nOutliers\n")?;
-            for outlier in &self.1 {
-                writeln!(f, "{} : {}", outlier.0, outlier.1)?;
-            }
-        }
-        writeln!(f)
-    }
-}
-
-fn heartbeat() {
-    print!(".");
-    std::io::stdout().flush().unwrap();
-}
-
-fn stats_for_start_word(start_word: &str, alt_words: bool) -> Result<Stats, String> {
-    let mut total_guesses: u32 = 0;
-    let mut outliers: Vec<Outlier> = Vec::new();
-    for &word in words::answers() {
-        let mut solver = solver::Solver::new(alt_words).with_start_word(start_word)?;
-        let setter = setter::Setter::from_word(word);
-        let mut guess;
-        loop {
-            guess = solver.guess();
-            let result = setter.check(guess);
-            if let [Clue::Right(_), Clue::Right(_), Clue::Right(_), Clue::Right(_), Clue::Right(_)] =
-                result
-            {
- //               heartbeat();
-                total_guesses += solver.guesses();
-                if solver.guesses() > 6 {
-                    outliers.push(Outlier(
-                        std::str::from_utf8(&guess).unwrap().into(),
-                        solver.guesses(),
-                    ));
-                }
-                break;
-            }
-            solver.filter_self(result);
-        }
-    }
-    Ok(Stats(
-        total_guesses as f32 / words::answers().len() as f32,
-        outliers,
-    ))
-}
-
-#[derive(Debug)]
-struct StartWordRanking(String, f32);
-
-fn find_optimal_start_word(alt_words: bool) -> Result<(), String> {
-    let answer_words = words::answers();
-    let initial_answer_count = answer_words.len() as f32;
-    let mut rankings: Vec<StartWordRanking> = Vec::new();
-
-    // Iterate through all candidate words
-    for &start_word in words::all(alt_words) {
-        let start_word_str = std::str::from_utf8(&start_word)
-            .map_err(|e| format!("Invalid UTF-8: {}", e))?
-            .to_string();
-        
-        let mut total_reduction: f32 = 0.0;
-        
-        // For each answer word, calculate the reduction in answer list size
-        for &answer_word in answer_words {
-            let mut solver = solver::Solver::new(alt_words).with_start_word(&start_word_str)?;
-            let setter = setter::Setter::from_word(answer_word);
-            
-            // Get the first guess
-            let guess = solver.guess();
-            
-            // Check the guess against the setter
-            let result = setter.check(guess);
-            
-            // Filter the solver with the result
-            solver.filter_self(result);
-            
-            // Calculate the reduction in answer list size
-            let remaining_count = solver.remaining() as f32;
-            let reduction = initial_answer_count - remaining_count;
-            total_reduction += reduction;
-        }
-        
-        // Calculate average reduction
-        let average_reduction = total_reduction / answer_words.len() as f32;
-        let score = StartWordRanking(start_word_str, average_reduction);
-        writeln!(std::io::stdout(), "Score: {} : {:.2}", score.0, score.1).unwrap();
-        // Keep track of top 10
-        rankings.push(score);
-        
-        // Sort and keep only top 10 if we have more than 10
-        if rankings.len() > 10 {
-            rankings.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-            rankings.truncate(10);
-        }
-        
-        heartbeat();
-    }
-    
-    // Final sort
-    rankings.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-    
-    println!("\n\nTop 10 optimal starting words (by average answer list reduction):");
-    for (i, ranking) in rankings.iter().enumerate() {
-        println!("{:2}. {} : {:.2}", i + 1, ranking.0, ranking.1);
-    }
-    
-    Ok(())
+    Words,
+    /// Analyses a finished game, given the target word followed by the
+    /// player's guesses, comparing each guess with the solver's choice
+    Analyse {
+        target: String,
+        #[arg(required = true)]
+        guesses: Vec<String>,
+    },
 }
 
-fn demo(target: &str, alt_words: bool) -> Result<(), String> {
-    let setter = setter::Setter::from_str(target)?;
-    let mut solver = solver::Solver::new(alt_words);
-    loop {
-        let guess = solver.guess();
-        let result = setter.check(guess);
-        solver.filter_self(result);
-
-        if let [Clue::Right(_), Clue::Right(_), Clue::Right(_), Clue::Right(_), Clue::Right(_)] =
-            result
-        {
-            println!(
-                "solved : {}",
-                std::str::from_utf8(&guess).map_err(|e| e.to_string())?
-            );
-            break;
+fn run(cli: Cli) -> Result<(), HmodeError> {
+    let options = Options::new(cli.alt_words, cli.rules, cli.format, cli.palette)
+        .with_second_guess_cache(cli.second_guess_cache.as_deref())?
+        .with_decision_cache(cli.decision_cache)?;
+    let result = match cli.command {
+        Command::Stats {
+            start_word,
+            shard,
+            output,
+        } => commands::stats(&options, &start_word, shard, output.as_deref()),
+        Command::Rank {
+            checkpoint,
+            resume,
+            shard,
+            shortlist,
+        } => commands::rank(&options, checkpoint.as_deref(), resume, shard, shortlist),
+        Command::Merge { files } => commands::merge(&options, &files),
+        Command::Demo { target, record } => commands::demo(&options, &target, record.as_deref()),
+        Command::Play {
+            seed,
+            daily,
+            record,
+            tui,
+        } => commands::play(&options, seed, daily.as_deref(), record.as_deref(), tui),
+        Command::Assist { record, tui } => commands::assist(&options, record.as_deref(), tui),
+        Command::Replay { file } => commands::replay(&options, &file),
+        Command::SecondGuesses { start, output } => {
+            commands::second_guesses(&options, start.as_deref(), output.as_deref())
         }
-        println!(
-            "Guessing : {}, {}",
-            std::str::from_utf8(&guess).map_err(|e| e.to_string())?,
-            solver.remaining()
-        );
-    }
-    Ok(())
+        Command::Serve { port } => commands::serve(&options, port),
+        Command::Words => commands::list_words(&options),
+        Command::Analyse { target, guesses } => commands::analyse(&options, &target, &guesses),
+    };
+    // The decisions are valid even if the run was interrupted or failed
+    options.save_decisions()?;
+    result
 }
 
-fn list_all_words(alt_words: bool) {
-    for &i in words::all(alt_words) {
-        println!("{}", std::str::from_utf8(&i).expect("Invalid utf8"));
-    }
-}
-
-fn main() -> Result<(), String> {
-    let cli = Cli::parse();
-    match cli {
-        Cli {
-            start_word: Some(s),
-            ..
-        } => {
-            println!("Calculating statistics for start word \"{s}\". This may take some time.");
-            println!("{}", stats_for_start_word(s.as_str(), cli.alt_words)?);
-            Ok(())
-        }
-        Cli {
-            find_optimal_start: true,
-            ..
-        } => {
-            println!("Finding optimal start word. This may take several minutes.");
-            find_optimal_start_word(cli.alt_words)?;
-            Ok(())
+/// Reports an error and exits with the error's exit code, so that scripts can
+/// tell the kinds of error apart.
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::from(e.exit_code())
         }
-        Cli { demo: Some(d), .. } => Ok(demo(d.as_str(), cli.alt_words)?),
-        Cli { .. } if cli.list_words => {
-            list_all_words(cli.alt_words);
-            Ok(())
-        }
-
-        _ => Err("Invalid parameters. Try 'hmode --help'".into()),
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn stats_display() {
-        let outliers: Vec<Outlier> =
-            [Outlier("table".into(), 7), Outlier("fable".into(), 7)].into();
-        let stats = Stats(3.14, outliers);
-        println!("{}", stats);
     }
 }
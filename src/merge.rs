@@ -0,0 +1,99 @@
+use crate::checkpoint::{CHECKPOINT_HEADER, Scores, StartWordRanking};
+use crate::error::HmodeError;
+use crate::results::{RESULTS_HEADER, StatsResults};
+use crate::shard::{self, Shard};
+use crate::stats::Stats;
+use crate::words;
+use std::collections::HashSet;
+
+/// The results merged from the files written by sharded runs.
+pub enum Merged {
+    /// The statistics of full games, from stats result files
+    Stats(Stats),
+    /// The start word rankings, best first, from rank checkpoints
+    Rankings(Vec<StartWordRanking>),
+}
+
+fn merge_rankings(files: &[String]) -> Result<(Merged, Vec<Option<Shard>>), HmodeError> {
+    let mut alt_words = None;
+    let mut shards = Vec::new();
+    let mut seen = HashSet::new();
+    let mut rankings = Vec::new();
+    for path in files {
+        let scores = Scores::load(path)?;
+        if alt_words.is_some_and(|a| a != scores.alt_words) {
+            return Err(HmodeError::InvalidArgument(
+                "The result files were written with different word lists".into(),
+            ));
+        }
+        alt_words = Some(scores.alt_words);
+        let candidates = shard::select(scores.shard, words::all(scores.alt_words)).len();
+        if scores.rankings.len() < candidates {
+            return Err(HmodeError::InvalidArgument(format!(
+                "The checkpoint {path} is from a rank run that was stopped before it finished"
+            )));
+        }
+        shards.push(scores.shard);
+        rankings.extend(
+            scores
+                .rankings
+                .into_iter()
+                .filter(|r| seen.insert(r.0.clone())),
+        );
+    }
+    rankings.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    Ok((Merged::Rankings(rankings), shards))
+}
+
+fn merge_stats(files: &[String]) -> Result<(Merged, Vec<Option<Shard>>), HmodeError> {
+    let mut run: Option<(String, bool)> = None;
+    let mut shards = Vec::new();
+    let mut seen = HashSet::new();
+    let mut solves = Vec::new();
+    for path in files {
+        let results = StatsResults::load(path)?;
+        if !results.complete {
+            return Err(HmodeError::InvalidArgument(format!(
+                "The result file {path} is from a stats run that was stopped before it finished"
+            )));
+        }
+        let this_run = (results.start_word, results.alt_words);
+        if run.as_ref().is_some_and(|r| *r != this_run) {
+            return Err(HmodeError::InvalidArgument(
+                "The result files were written with different start words or word lists".into(),
+            ));
+        }
+        run = Some(this_run);
+        shards.push(results.shard);
+        solves.extend(
+            results
+                .solves
+                .into_iter()
+                .filter(|s| seen.insert(s.0.clone())),
+        );
+    }
+    Ok((Merged::Stats(Stats::from_solves(&solves)), shards))
+}
+
+/// Merges the result files written by sharded stats or rank runs, which must
+/// all come from stats runs or all from rank runs. Returns the merged results,
+/// and a description of the problem if the files' shards don't cover the
+/// whole list exactly once.
+pub fn merge(files: &[String]) -> Result<(Merged, Option<String>), HmodeError> {
+    let mut headers = Vec::new();
+    for path in files {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| HmodeError::io(format!("reading file {path}"), e))?;
+        headers.push(text.lines().next().unwrap_or_default().trim().to_string());
+    }
+    let (merged, shards) = if headers.iter().all(|h| h == CHECKPOINT_HEADER) {
+        merge_rankings(files)?
+    } else if headers.iter().all(|h| h == RESULTS_HEADER) {
+        merge_stats(files)?
+    } else {
+        return Err(HmodeError::InvalidArgument(
+            "The files to merge must all be stats result files or all be rank checkpoints".into(),
+        ));
+    };
+    Ok((merged, shard::check_complete(&shards)))
+}
@@ -0,0 +1,162 @@
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+// How often the progress bar is redrawn on a terminal, and how often a log
+// line is written otherwise
+const BAR_INTERVAL: Duration = Duration::from_millis(200);
+const LOG_INTERVAL: Duration = Duration::from_secs(10);
+const BAR_WIDTH: usize = 30;
+
+/// Returns true if the current stats, rank or second guess run has been asked
+/// to stop.
+pub fn cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Asks the current stats, rank or second guess run to stop at the next item,
+/// so that it returns the results gathered so far, e.g. from a Ctrl-C handler.
+/// Returns true if the run had already been asked to stop.
+pub fn cancel() -> bool {
+    CANCELLED.swap(true, Ordering::SeqCst)
+}
+
+/// Clears a request to stop, so that the next run goes to the end.
+pub fn reset_cancelled() {
+    CANCELLED.store(false, Ordering::SeqCst);
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+/// Tracks the progress of a long run through a known number of items, and
+/// reports it on stderr. On a terminal this is drawn as a progress bar,
+/// otherwise a log line is written periodically.
+pub struct Progress {
+    label: String,
+    total: usize,
+    done: usize,
+    started: Instant,
+    last_report: Option<Instant>,
+    tty: bool,
+}
+
+impl Progress {
+    pub fn new(label: &str, total: usize) -> Self {
+        Progress {
+            label: label.into(),
+            total,
+            done: 0,
+            started: Instant::now(),
+            last_report: None,
+            tty: std::io::stderr().is_terminal(),
+        }
+    }
+
+    /// Items completed per second since the run started.
+    pub fn throughput(&self) -> f64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            self.done as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// Estimated time until all items are complete, based on the throughput so far.
+    pub fn eta(&self) -> Option<Duration> {
+        let rate = self.throughput();
+        if rate > 0.0 {
+            let remaining = self.total.saturating_sub(self.done) as f64;
+            Some(Duration::from_secs_f64(remaining / rate))
+        } else {
+            None
+        }
+    }
+
+    /// Records that one more item is complete, reporting progress if it is due.
+    pub fn inc(&mut self) {
+        self.done += 1;
+        let interval = if self.tty { BAR_INTERVAL } else { LOG_INTERVAL };
+        if self.last_report.is_none_or(|t| t.elapsed() >= interval) {
+            self.report();
+        }
+    }
+
+    fn summary(&self) -> String {
+        let eta = self
+            .eta()
+            .map(format_duration)
+            .unwrap_or_else(|| "-".into());
+        format!(
+            "{}/{} {:.1}/s ETA {eta}",
+            self.done,
+            self.total,
+            self.throughput()
+        )
+    }
+
+    fn report(&mut self) {
+        self.last_report = Some(Instant::now());
+        let mut stderr = std::io::stderr();
+        if self.tty {
+            let filled = (self.done * BAR_WIDTH).checked_div(self.total).unwrap_or(0);
+            _ = write!(
+                stderr,
+                "\r\x1b[K{} [{}{}] {}",
+                self.label,
+                "#".repeat(filled),
+                "-".repeat(BAR_WIDTH - filled),
+                self.summary()
+            );
+            _ = stderr.flush();
+        } else {
+            _ = writeln!(stderr, "{} : {}", self.label, self.summary());
+        }
+    }
+
+    /// Reports the final position, and leaves the terminal ready for normal output.
+    pub fn finish(&mut self) {
+        self.report();
+        if self.tty {
+            _ = writeln!(std::io::stderr());
+        }
+        if cancelled() {
+            eprintln!(
+                "Interrupted after {} of {} items, showing partial results",
+                self.done, self.total
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_duration_uses_largest_units() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(422)), "7m02s");
+        assert_eq!(format_duration(Duration::from_secs(3720)), "1h02m");
+    }
+
+    #[test]
+    fn progress_counts_items() {
+        let mut progress = Progress::new("test", 4);
+        assert_eq!(progress.eta(), None);
+        progress.inc();
+        progress.inc();
+        assert_eq!(progress.done, 2);
+        assert!(progress.throughput() > 0.0);
+        assert!(progress.eta().is_some());
+    }
+}
@@ -0,0 +1,253 @@
+//! Python bindings, built as an extension module with maturin (see
+//! pyproject.toml). The module is imported as `hmode`:
+//!   import hmode
+//!   solver = hmode.Solver()
+//!   solver.feed_clue("tares", "--Y--")
+//!   solver.suggestions(5)
+//!   hmode.Setter("crook").check("tares")
+//!   hmode.stats("tares", shard="1/20").mean
+
+use crate::assistant::Assistant;
+use crate::error::HmodeError;
+use crate::progress;
+use crate::setter::{self, RuleSet};
+use crate::shard::Shard;
+use crate::stats::{self, Solve, SolverCaches};
+use crate::words::{self, WdlWord};
+use pyo3::exceptions::{PyKeyboardInterrupt, PyOSError, PyValueError};
+use pyo3::prelude::*;
+use std::time::Duration;
+
+// How often a long calculation checks for Ctrl-C
+const SIGNAL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn py_error(e: HmodeError) -> PyErr {
+    match e {
+        HmodeError::Io { .. } => PyOSError::new_err(e.to_string()),
+        HmodeError::Interrupted(_) => PyKeyboardInterrupt::new_err(e.to_string()),
+        _ => PyValueError::new_err(e.to_string()),
+    }
+}
+
+fn word_string(word: &WdlWord) -> String {
+    String::from_utf8_lossy(word).into_owned()
+}
+
+fn rules(hard_mode: bool) -> RuleSet {
+    if hard_mode {
+        RuleSet::Hard
+    } else {
+        RuleSet::Normal
+    }
+}
+
+/// Suggests guesses for a game played elsewhere, from the guesses made and
+/// their clue patterns of G, Y and -.
+#[pyclass(name = "Solver")]
+pub struct PySolver {
+    assistant: Assistant,
+}
+
+#[pymethods]
+impl PySolver {
+    #[new]
+    #[pyo3(signature = (alt_words = false, hard_mode = true))]
+    fn new(alt_words: bool, hard_mode: bool) -> Self {
+        PySolver {
+            assistant: Assistant::new(alt_words, rules(hard_mode)),
+        }
+    }
+
+    /// Records a guess and its clue pattern, e.g. "--Y--". Raises ValueError,
+    /// leaving the solver as it was, if the clue breaks the rules or leaves no
+    /// possible answer.
+    fn feed_clue(&mut self, guess: &str, pattern: &str) -> PyResult<()> {
+        self.assistant
+            .feed_clue(guess, pattern)
+            .map(|_| ())
+            .map_err(py_error)
+    }
+
+    /// The guess the solver would make next.
+    fn next_guess(&self) -> PyResult<String> {
+        self.assistant
+            .next_guess()
+            .map(|w| word_string(&w))
+            .map_err(py_error)
+    }
+
+    /// The solver's best guesses, best first.
+    #[pyo3(signature = (count = 5))]
+    fn suggestions(&self, count: usize) -> PyResult<Vec<String>> {
+        let suggestions = self.assistant.suggestions(count).map_err(py_error)?;
+        Ok(suggestions.iter().map(word_string).collect())
+    }
+
+    /// The number of answer words that match the clues so far.
+    #[getter]
+    fn remaining(&self) -> usize {
+        self.assistant.remaining()
+    }
+}
+
+/// Knows the answer word and gives the clue pattern for each guess. The
+/// answer is the given word, or chosen from the seed, or else at random.
+#[pyclass(name = "Setter")]
+pub struct PySetter {
+    setter: setter::Setter,
+    alt_words: bool,
+}
+
+#[pymethods]
+impl PySetter {
+    #[new]
+    #[pyo3(signature = (word = None, seed = None, alt_words = false))]
+    fn new(word: Option<&str>, seed: Option<u64>, alt_words: bool) -> PyResult<Self> {
+        let setter = match (word, seed) {
+            (Some(word), _) => setter::Setter::from_word(
+                words::to_static_word(word, true, alt_words).map_err(py_error)?,
+            ),
+            (None, Some(seed)) => setter::Setter::from_seed(seed),
+            (None, None) => setter::Setter::new(),
+        };
+        Ok(PySetter { setter, alt_words })
+    }
+
+    /// The clue pattern for a guess, e.g. "-GY--".
+    fn check(&self, guess: &str) -> PyResult<String> {
+        let guess = words::to_static_word(guess, false, self.alt_words).map_err(py_error)?;
+        Ok(setter::pattern_string(&self.setter.check(guess)))
+    }
+}
+
+/// The statistics of full games for a start word.
+#[pyclass(name = "Stats", get_all)]
+pub struct PyStats {
+    mean: f32,
+    max: u32,
+    fails: usize,
+    /// The answers needing more than 6 guesses, with their number of guesses
+    outliers: Vec<(String, u32)>,
+}
+
+fn solve_answers(
+    py: Python<'_>,
+    start_word: &str,
+    alt_words: bool,
+    shard: Option<&str>,
+) -> PyResult<Vec<Solve>> {
+    let shard = shard
+        .map(str::parse::<Shard>)
+        .transpose()
+        .map_err(py_error)?;
+    let caches = SolverCaches::new(alt_words);
+    interruptible(py, || {
+        stats::solve_answers(start_word, alt_words, shard, &caches)
+    })
+}
+
+// Runs a long calculation on another thread, checking for Python signals
+// while it runs, so that Ctrl-C stops it with a KeyboardInterrupt
+fn interruptible<T: Send>(
+    py: Python<'_>,
+    f: impl FnOnce() -> Result<T, HmodeError> + Send,
+) -> PyResult<T> {
+    progress::reset_cancelled();
+    std::thread::scope(|scope| {
+        let run = scope.spawn(f);
+        while !run.is_finished() {
+            py.detach(|| std::thread::sleep(SIGNAL_INTERVAL));
+            if let Err(e) = py.check_signals() {
+                // The run stops at the next answer, and its results are dropped
+                progress::cancel();
+                _ = run.join();
+                progress::reset_cancelled();
+                return Err(e);
+            }
+        }
+        run.join()
+            .expect("The calculation doesn't panic")
+            .map_err(py_error)
+    })
+}
+
+/// Solves every answer, or the answers in a shard such as "1/20", from the
+/// start word, returning each answer with its number of guesses. The solver
+/// always plays by the hard mode rules.
+#[pyfunction(name = "solve_answers")]
+#[pyo3(signature = (start_word = "tares", alt_words = false, shard = None))]
+fn py_solve_answers(
+    py: Python<'_>,
+    start_word: &str,
+    alt_words: bool,
+    shard: Option<&str>,
+) -> PyResult<Vec<(String, u32)>> {
+    let solves = solve_answers(py, start_word, alt_words, shard)?;
+    Ok(solves.into_iter().map(|s| (s.0, s.1)).collect())
+}
+
+/// The statistics of full games for the start word, as the stats command
+/// gives them.
+#[pyfunction(name = "stats")]
+#[pyo3(signature = (start_word = "tares", alt_words = false, shard = None))]
+fn py_stats(
+    py: Python<'_>,
+    start_word: &str,
+    alt_words: bool,
+    shard: Option<&str>,
+) -> PyResult<PyStats> {
+    let solves = solve_answers(py, start_word, alt_words, shard)?;
+    let stats = stats::Stats::from_solves(&solves);
+    Ok(PyStats {
+        mean: stats.mean,
+        max: stats.max,
+        fails: stats.fails(),
+        outliers: stats.outliers.into_iter().map(|o| (o.0, o.1)).collect(),
+    })
+}
+
+#[pymodule]
+fn hmode(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySolver>()?;
+    m.add_class::<PySetter>()?;
+    m.add_class::<PyStats>()?;
+    m.add_function(wrap_pyfunction!(py_solve_answers, m)?)?;
+    m.add_function(wrap_pyfunction!(py_stats, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn use_the_module_from_python() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "hmode").unwrap();
+            hmode(&module).unwrap();
+            let globals = pyo3::types::PyDict::new(py);
+            globals.set_item("hmode", module).unwrap();
+            let script = c"
+solver = hmode.Solver()
+assert solver.next_guess() == 'tares'
+solver.feed_clue('tares', '--Y--')
+assert solver.remaining == 93
+assert solver.suggestions(2) == ['prion', 'grind']
+try:
+    solver.feed_clue('pious', '-----')
+    assert False
+except ValueError as e:
+    assert 'R' in str(e)
+
+assert hmode.Setter('crook').check('tares') == '--Y--'
+assert hmode.Setter(seed=1).check('tares') == hmode.Setter(seed=1).check('tares')
+
+stats = hmode.stats('tares', shard='1/500')
+assert stats.max >= 3 and stats.fails == 0
+assert hmode.solve_answers(shard='1/500')[0] == ('cigar', 3)
+";
+            py.run(script, Some(&globals), None).unwrap();
+        });
+    }
+}
@@ -0,0 +1,243 @@
+use crate::error::HmodeError;
+use crate::line_file;
+use crate::setter::{self, CheckResult, Setter};
+use crate::solver::Solver;
+use crate::words::{WdlWord, to_static_word, word_str};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// A game log is a line file. Header lines are a keyword followed by a value,
+// and each turn is a line in the form
+//   guess <word> <pattern> <remaining answers> <unix timestamp>
+const LOG_HEADER: &str = "# hmode game log";
+
+fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Writes a log of a game as it is played, so that it can be replayed later.
+pub struct Recorder {
+    out: BufWriter<File>,
+}
+
+impl Recorder {
+    /// Creates the log file for a game in the given mode, e.g. "play", with
+    /// the target word if it is known.
+    pub fn create(
+        path: &str,
+        mode: &str,
+        target: Option<WdlWord>,
+        alt_words: bool,
+    ) -> Result<Self, HmodeError> {
+        let file =
+            File::create(path).map_err(|e| HmodeError::io(format!("creating file {path}"), e))?;
+        let mut recorder = Recorder {
+            out: BufWriter::new(file),
+        };
+        recorder.write_line(LOG_HEADER)?;
+        recorder.write_line(&format!("mode {mode}"))?;
+        recorder.write_line(&format!("alt-words {alt_words}"))?;
+        if let Some(target) = target {
+            recorder.write_line(&format!("target {}", word_str(&target)))?;
+        }
+        Ok(recorder)
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), HmodeError> {
+        writeln!(self.out, "{line}")
+            .and_then(|_| self.out.flush())
+            .map_err(|e| HmodeError::io("writing game log", e))
+    }
+
+    /// Records a guess, its clues and the number of answers they leave.
+    pub fn record(
+        &mut self,
+        guess: WdlWord,
+        result: &CheckResult,
+        remaining: usize,
+    ) -> Result<(), HmodeError> {
+        self.write_line(&format!(
+            "guess {} {} {remaining} {}",
+            word_str(&guess),
+            setter::pattern_string(result),
+            timestamp()
+        ))
+    }
+}
+
+/// A recorded guess, with its clues, the number of answers they leave and
+/// when it was made.
+#[derive(Debug, PartialEq)]
+pub struct Turn {
+    pub guess: WdlWord,
+    pub result: CheckResult,
+    pub remaining: usize,
+    pub timestamp: u64,
+}
+
+/// A game log written by a Recorder.
+#[derive(Debug, PartialEq)]
+pub struct GameLog {
+    pub mode: String,
+    pub alt_words: bool,
+    pub target: Option<WdlWord>,
+    pub turns: Vec<Turn>,
+}
+
+impl GameLog {
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let mut log = GameLog {
+            mode: String::new(),
+            alt_words: false,
+            target: None,
+            turns: Vec::new(),
+        };
+        for line in line_file::lines(text) {
+            match line.fields.as_slice() {
+                ["mode", mode] => log.mode = mode.to_string(),
+                ["alt-words", alt] => {
+                    log.alt_words = alt
+                        .parse()
+                        .map_err(|_| line.error(format!("invalid value '{alt}'")))?
+                }
+                ["target", target] => {
+                    log.target =
+                        Some(to_static_word(target, true, false).map_err(|e| line.error(e))?)
+                }
+                ["guess", guess, pattern, remaining, timestamp] => {
+                    let guess =
+                        to_static_word(guess, false, log.alt_words).map_err(|e| line.error(e))?;
+                    log.turns.push(Turn {
+                        guess,
+                        result: setter::parse_pattern(guess, pattern).map_err(|e| line.error(e))?,
+                        remaining: remaining
+                            .parse()
+                            .map_err(|_| line.error(format!("invalid count '{remaining}'")))?,
+                        timestamp: timestamp
+                            .parse()
+                            .map_err(|_| line.error(format!("invalid timestamp '{timestamp}'")))?,
+                    })
+                }
+                _ => return Err(line.unrecognised()),
+            }
+        }
+        Ok(log)
+    }
+
+    pub fn load(path: &str) -> Result<Self, HmodeError> {
+        line_file::load(path, Self::parse)
+    }
+
+    /// Re-runs the game through the Setter and Solver, finding what hmode
+    /// would have guessed at each step, and checking that the recorded clues
+    /// are consistent with the target word and with each other.
+    pub fn replay(&self) -> Replay {
+        let setter = self.target.map(Setter::from_word);
+        let mut solver = Solver::new(self.alt_words);
+        let mut replay = Replay {
+            turns: Vec::new(),
+            problems: Vec::new(),
+        };
+        for (i, turn) in self.turns.iter().enumerate() {
+            let step = i + 1;
+            let solver_guess = solver.clone().guess().ok();
+            if let Some(setter) = &setter {
+                let expected = setter.check(turn.guess);
+                if expected != turn.result {
+                    replay.problems.push(format!(
+                        "Step {step} : recorded clue {} for {} should be {}",
+                        setter::pattern_string(&turn.result),
+                        word_str(&turn.guess),
+                        setter::pattern_string(&expected)
+                    ));
+                }
+            }
+            solver.record_guess(turn.guess, turn.result);
+            if solver.remaining() != turn.remaining {
+                replay.problems.push(format!(
+                    "Step {step} : recorded {} remaining words, but the clues leave {}",
+                    turn.remaining,
+                    solver.remaining()
+                ));
+            }
+            replay.turns.push(ReplayedTurn {
+                guess: turn.guess,
+                result: turn.result,
+                solver_guess,
+                remaining: solver.remaining(),
+            });
+        }
+        replay
+    }
+}
+
+/// A recorded turn, replayed through the Setter and Solver.
+#[derive(Debug, PartialEq)]
+pub struct ReplayedTurn {
+    pub guess: WdlWord,
+    pub result: CheckResult,
+    /// The guess the solver would have made, or none if the earlier clues
+    /// contradict each other
+    pub solver_guess: Option<WdlWord>,
+    /// The number of answers that match the clues so far
+    pub remaining: usize,
+}
+
+/// A game log re-run through the Setter and Solver.
+#[derive(Debug, PartialEq)]
+pub struct Replay {
+    pub turns: Vec<ReplayedTurn>,
+    /// The recorded clues that are inconsistent with the target word or with
+    /// each other
+    pub problems: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_LOG: &str = "# hmode game log
+mode demo
+alt-words false
+target crook
+guess tares --Y-- 93 1700000000
+guess crook GGGGG 1 1700000010
+";
+
+    #[test]
+    fn parse_reads_headers_and_turns() {
+        let log = GameLog::parse(SAMPLE_LOG).unwrap();
+        assert_eq!(log.mode, "demo");
+        assert_eq!(log.target, Some(*b"crook"));
+        assert_eq!(log.turns.len(), 2);
+        assert_eq!(log.turns[0].guess, *b"tares");
+        assert_eq!(log.turns[0].remaining, 93);
+        assert_eq!(log.turns[1].timestamp, 1_700_000_010);
+        assert!(setter::is_solved(&log.turns[1].result));
+    }
+
+    #[test]
+    fn parse_rejects_bad_entries() {
+        assert!(GameLog::parse("guess tares -Y--- many 0").is_err());
+        assert!(GameLog::parse("guess tares -Y-- 1 0").is_err());
+        assert!(GameLog::parse("something else").is_err());
+    }
+
+    #[test]
+    fn replay_accepts_consistent_log() {
+        let replay = GameLog::parse(SAMPLE_LOG).unwrap().replay();
+        assert_eq!(replay.problems, Vec::<String>::new());
+        assert_eq!(replay.turns[0].solver_guess, Some(*b"tares"));
+        assert_eq!(replay.turns[1].remaining, 1);
+    }
+
+    #[test]
+    fn replay_detects_wrong_clue() {
+        let log = GameLog::parse(&SAMPLE_LOG.replace("--Y--", "-YY--")).unwrap();
+        assert!(!log.replay().problems.is_empty());
+    }
+}
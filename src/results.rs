@@ -0,0 +1,108 @@
+use crate::error::HmodeError;
+use crate::line_file;
+use crate::shard::Shard;
+use crate::stats::Solve;
+
+// A stats result file is a line file recording the number of guesses the
+// solver needed for each answer word, in the form
+//   solved <word> <guesses>
+// Result files from different shards can be merged to give the statistics for
+// the whole list, but only those marked `complete true`, as a run stopped with
+// Ctrl-C leaves some answers unsolved.
+pub const RESULTS_HEADER: &str = "# hmode stats results";
+
+#[derive(Debug, PartialEq)]
+pub struct StatsResults {
+    pub start_word: String,
+    pub alt_words: bool,
+    pub shard: Option<Shard>,
+    pub complete: bool,
+    pub solves: Vec<Solve>,
+}
+
+impl StatsResults {
+    pub fn save(&self, path: &str) -> Result<(), HmodeError> {
+        let mut text = format!(
+            "{RESULTS_HEADER}\nstart-word {}\nalt-words {}\n",
+            self.start_word, self.alt_words
+        );
+        if let Some(shard) = self.shard {
+            text.push_str(&format!("shard {shard}\n"));
+        }
+        text.push_str(&format!("complete {}\n", self.complete));
+        for solve in &self.solves {
+            text.push_str(&format!("solved {} {}\n", solve.0, solve.1));
+        }
+        line_file::save(path, &text)
+    }
+
+    pub fn load(path: &str) -> Result<Self, HmodeError> {
+        line_file::load(path, Self::parse)
+    }
+
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let mut results = StatsResults {
+            start_word: String::new(),
+            alt_words: false,
+            shard: None,
+            complete: false,
+            solves: Vec::new(),
+        };
+        for line in line_file::lines(text) {
+            match line.fields.as_slice() {
+                ["start-word", word] => results.start_word = word.to_string(),
+                ["alt-words", alt] => {
+                    results.alt_words = alt
+                        .parse()
+                        .map_err(|_| line.error(format!("invalid value '{alt}'")))?;
+                }
+                ["complete", complete] => {
+                    results.complete = complete
+                        .parse()
+                        .map_err(|_| line.error(format!("invalid value '{complete}'")))?;
+                }
+                ["shard", shard] => {
+                    results.shard = Some(shard.parse().map_err(|e: HmodeError| line.error(e))?)
+                }
+                ["solved", word, guesses] => results.solves.push(Solve(
+                    word.to_string(),
+                    guesses
+                        .parse()
+                        .map_err(|_| line.error(format!("invalid guess count '{guesses}'")))?,
+                )),
+                _ => return Err(line.unrecognised()),
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("hmode-results-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let results = StatsResults {
+            start_word: "tares".into(),
+            alt_words: false,
+            shard: Some(Shard::new(1, 2).unwrap()),
+            complete: true,
+            solves: vec![Solve("cigar".into(), 3), Solve("rebut".into(), 4)],
+        };
+        results.save(path).unwrap();
+        assert_eq!(StatsResults::load(path), Ok(results));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn parse_rejects_bad_counts() {
+        assert!(StatsResults::parse("solved cigar three").is_err());
+        assert!(StatsResults::parse("shard 3/2").is_err());
+        assert!(StatsResults::parse("complete maybe").is_err());
+        // Files without the flag are treated as partial
+        assert!(!StatsResults::parse("solved cigar 3").unwrap().complete);
+    }
+}
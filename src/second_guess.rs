@@ -0,0 +1,263 @@
+use crate::error::HmodeError;
+use crate::line_file::{self, bad_line};
+use crate::progress::{self, Progress};
+use crate::setter::{self, CheckResult, PATTERN_COUNT};
+use crate::solver::{STRATEGY, Solver};
+use crate::words::{WdlWord, to_static_word, word_str};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+// A second guess table is a line file, with a line for each clue pattern that
+// the start word can produce, in the form
+//   pattern <pattern> <remaining answers> <second guess>
+const TABLE_HEADER: &str = "# hmode second guess table";
+
+// A table loaded from the cache directory, or none if it couldn't be loaded,
+// by start word and word list
+type DefaultTable = ((WdlWord, bool), Option<Arc<SecondGuessTable>>);
+static DEFAULT_TABLES: Mutex<Vec<DefaultTable>> = Mutex::new(Vec::new());
+
+/// The file in the user's cache directory where the second-guesses command
+/// saves the table for a start word, so that later runs can find it. There is
+/// none if neither XDG_CACHE_HOME nor HOME is set.
+pub fn default_path(start_word: WdlWord, alt_words: bool) -> Option<PathBuf> {
+    let dir = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+    let list = if alt_words { "-alt" } else { "" };
+    let name = format!(
+        "second-guesses-{}{list}.txt",
+        String::from_utf8_lossy(&start_word)
+    );
+    Some(dir.join("hmode").join(name))
+}
+
+/// The table saved in the cache directory for the start word and word list,
+/// if there is one. The file is read once per process, and a table that can't
+/// be read, e.g. because it was written for a different strategy, is ignored
+/// as the solver can search for the second guess instead.
+pub fn default_table(start_word: WdlWord, alt_words: bool) -> Option<Arc<SecondGuessTable>> {
+    let mut tables = DEFAULT_TABLES.lock().unwrap();
+    if let Some((_, table)) = tables.iter().find(|(key, _)| *key == (start_word, alt_words)) {
+        return table.clone();
+    }
+    let table = default_path(start_word, alt_words)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|text| SecondGuessTable::parse(&text).ok())
+        .filter(|t| t.start_word == start_word && t.alt_words == alt_words)
+        .map(Arc::new);
+    tables.push(((start_word, alt_words), table.clone()));
+    table
+}
+
+/// The solver's second guess for a clue pattern, with the number of answers
+/// the pattern leaves.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct SecondGuess {
+    pub remaining: usize,
+    pub guess: WdlWord,
+}
+
+/// The solver's second guess for every clue pattern of a fixed start word.
+/// As the first guess is always the same, the second guess depends only on
+/// the first clue, so it can be computed once and looked up by the solver.
+#[derive(Debug, PartialEq)]
+pub struct SecondGuessTable {
+    pub start_word: WdlWord,
+    pub alt_words: bool,
+    entries: Vec<Option<SecondGuess>>,
+}
+
+impl SecondGuessTable {
+    /// Builds the table by running the solver for every pattern. Patterns that
+    /// no answer word can produce have no entry. The solver uses no tables or
+    /// caches, so every guess is searched for afresh.
+    pub fn build(start_word: &str, alt_words: bool) -> Result<Self, HmodeError> {
+        let mut first = Solver::new(alt_words).with_start_word(start_word)?;
+        let start_word = first.guess()?;
+        let mut entries = Vec::with_capacity(PATTERN_COUNT);
+        let mut progress = Progress::new("Patterns", PATTERN_COUNT);
+        for index in 0..PATTERN_COUNT {
+            if progress::cancelled() {
+                progress.finish();
+                return Err(HmodeError::Interrupted(
+                    "Interrupted before the second guess table was complete".into(),
+                ));
+            }
+            let mut solver = first.clone();
+            solver.filter_self(setter::pattern_from_index(start_word, index));
+            entries.push(solver.guess().ok().map(|guess| SecondGuess {
+                remaining: solver.remaining(),
+                guess,
+            }));
+            progress.inc();
+        }
+        progress.finish();
+        Ok(SecondGuessTable {
+            start_word,
+            alt_words,
+            entries,
+        })
+    }
+
+    /// Returns the second guess for the clue given by the start word.
+    pub fn get(&self, first_clue: &CheckResult) -> Option<SecondGuess> {
+        if setter::guessed_word(first_clue) != self.start_word {
+            return None;
+        }
+        self.entries[setter::pattern_index(first_clue)]
+    }
+
+    /// Returns every clue pattern the start word can receive, with its entry.
+    pub fn patterns(&self) -> impl Iterator<Item = (CheckResult, Option<SecondGuess>)> + '_ {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (setter::pattern_from_index(self.start_word, index), *entry))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), HmodeError> {
+        // The word list comes first, as it is needed to check the start word
+        let mut text = format!(
+            "{TABLE_HEADER}\nstrategy {STRATEGY}\nalt-words {}\nstart-word {}\n",
+            self.alt_words,
+            word_str(&self.start_word)
+        );
+        for (index, entry) in self.entries.iter().enumerate() {
+            if let Some(entry) = entry {
+                let pattern = setter::pattern_from_index(self.start_word, index);
+                text.push_str(&format!(
+                    "pattern {} {} {}\n",
+                    setter::pattern_string(&pattern),
+                    entry.remaining,
+                    word_str(&entry.guess)
+                ));
+            }
+        }
+        line_file::save(path, &text)
+    }
+
+    /// Saves the table to its default path, creating the cache directory if
+    /// needed, and returns the path.
+    pub fn save_default(&self) -> Result<PathBuf, HmodeError> {
+        let path = default_path(self.start_word, self.alt_words).ok_or_else(|| {
+            HmodeError::InvalidArgument(
+                "There is no cache directory, as neither XDG_CACHE_HOME nor HOME is set".into(),
+            )
+        })?;
+        let dir = path.parent().expect("The path is in a directory");
+        fs::create_dir_all(dir)
+            .map_err(|e| HmodeError::io(format!("creating directory {}", dir.display()), e))?;
+        self.save(&path.to_string_lossy())?;
+        Ok(path)
+    }
+
+    pub fn load(path: &str) -> Result<Self, HmodeError> {
+        line_file::load(path, Self::parse)
+    }
+
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        // The words are checked once the whole header has been read, as the
+        // word list decides which words are valid
+        let mut start_word = None;
+        let mut alt_words = false;
+        let mut patterns = Vec::new();
+        for line in line_file::lines(text) {
+            let n = line.number;
+            match line.fields.as_slice() {
+                ["strategy", strategy] if *strategy != STRATEGY => {
+                    return Err(line.error("the table was written with a different strategy"));
+                }
+                ["strategy", _] => {}
+                ["start-word", word] => start_word = Some((n, *word)),
+                ["alt-words", alt] => {
+                    alt_words = alt
+                        .parse()
+                        .map_err(|_| line.error(format!("invalid value '{alt}'")))?
+                }
+                ["pattern", pattern, remaining, guess] => {
+                    patterns.push((n, *pattern, *remaining, *guess))
+                }
+                _ => return Err(line.unrecognised()),
+            }
+        }
+        let (n, word) = start_word.ok_or("missing start-word")?;
+        let start_word = to_static_word(word, false, alt_words).map_err(|e| bad_line(n, e))?;
+        let mut entries = vec![None; PATTERN_COUNT];
+        for (n, pattern, remaining, guess) in patterns {
+            let result = setter::parse_pattern(start_word, pattern).map_err(|e| bad_line(n, e))?;
+            entries[setter::pattern_index(&result)] = Some(SecondGuess {
+                remaining: remaining
+                    .parse()
+                    .map_err(|_| bad_line(n, format!("invalid count '{remaining}'")))?,
+                guess: to_static_word(guess, false, alt_words).map_err(|e| bad_line(n, e))?,
+            });
+        }
+        Ok(SecondGuessTable {
+            start_word,
+            alt_words,
+            entries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setter::Setter;
+
+    fn sample_table() -> SecondGuessTable {
+        let mut entries = vec![None; PATTERN_COUNT];
+        let result = Setter::from_word(*b"crook").check(*b"tares");
+        entries[setter::pattern_index(&result)] = Some(SecondGuess {
+            remaining: 93,
+            guess: *b"prion",
+        });
+        SecondGuessTable {
+            start_word: *b"tares",
+            alt_words: false,
+            entries,
+        }
+    }
+
+    #[test]
+    fn get_looks_up_first_clue() {
+        let table = sample_table();
+        let crook = Setter::from_word(*b"crook");
+        assert_eq!(table.get(&crook.check(*b"tares")).unwrap().guess, *b"prion");
+        assert_eq!(
+            table.get(&Setter::from_word(*b"tares").check(*b"tares")),
+            None
+        );
+        assert_eq!(table.get(&crook.check(*b"crane")), None);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("hmode-second-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let table = sample_table();
+        table.save(path).unwrap();
+        assert_eq!(SecondGuessTable::load(path), Ok(table));
+
+        // A start word only in the alternative list needs the word list first
+        let alt_table = SecondGuessTable {
+            start_word: *b"alton",
+            alt_words: true,
+            entries: vec![None; PATTERN_COUNT],
+        };
+        alt_table.save(path).unwrap();
+        assert_eq!(SecondGuessTable::load(path), Ok(alt_table));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn parse_rejects_other_strategies() {
+        let text = "strategy min-entropy\nstart-word tares\n";
+        assert!(SecondGuessTable::parse(text).is_err());
+        assert!(SecondGuessTable::parse("start-word alton\nalt-words true\n").is_ok());
+        assert!(SecondGuessTable::parse("start-word alton\n").is_err());
+    }
+}
@@ -0,0 +1,385 @@
+use hmode::{
+    CheckResult, DEFAULT_START_WORD, HmodeError, MAX_GUESSES, RuleSet, Setter, Shard, SolverCaches,
+    Stats, WdlWord, guessed_word, is_solved, parse_pattern, pattern_string, solve_answers,
+    to_static_word, word_str,
+};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tiny_http::{Header, Request, Response};
+
+/// The number of suggestions returned if the request doesn't give a count.
+const DEFAULT_SUGGESTIONS: usize = 5;
+
+/// The number of games kept. Starting a game beyond this forgets the oldest.
+const MAX_GAMES: usize = 10_000;
+
+struct Game {
+    setter: Setter,
+    history: Vec<CheckResult>,
+}
+
+impl Game {
+    fn solved(&self) -> bool {
+        self.history.last().is_some_and(is_solved)
+    }
+
+    fn lost(&self) -> bool {
+        !self.solved() && self.history.len() >= MAX_GUESSES
+    }
+
+    // Whether the game is over, with the answer once it is
+    fn state(&self) -> Value {
+        let mut state = json!({
+            "guesses": self.history.len(),
+            "solved": self.solved(),
+            "lost": self.lost(),
+        });
+        if self.solved() || self.lost() {
+            state["answer"] = json!(word_str(&self.setter.word()));
+        }
+        state
+    }
+}
+
+#[derive(Default)]
+struct Games {
+    next_id: u64,
+    // Ordered by id, so the oldest game is first
+    games: BTreeMap<u64, Game>,
+}
+
+/// A JSON API over HTTP for games, solver suggestions and stats, for web
+/// front ends and bots running on the same machine. The endpoints are
+///   POST /games                  starts a game, e.g. {"seed": 42}, returning its id
+///   GET  /games/<id>             the guesses made so far
+///   POST /games/<id>/guesses     submits a guess, e.g. {"guess": "tares"}, returning its
+///                                pattern. A game is lost after 6 guesses without the answer
+///   POST /suggestions            the solver's best guesses, given
+///                                {"history": [{"guess": "tares", "pattern": "-GY--"}], "count": 5}
+///   GET  /stats?start_word=tares&shard=1/20   full game stats for a start word, one
+///                                run at a time
+/// Errors are returned as {"error": <message>} with a 4xx or 5xx status.
+pub struct Api {
+    alt_words: bool,
+    rules: RuleSet,
+    caches: SolverCaches,
+    games: Mutex<Games>,
+    // Held while stats are calculated, as a full run takes minutes of CPU
+    stats_running: Mutex<()>,
+}
+
+type ApiResult = Result<(u16, Value), HmodeError>;
+
+impl Api {
+    pub fn new(alt_words: bool, rules: RuleSet, caches: SolverCaches) -> Self {
+        Api {
+            alt_words,
+            rules,
+            caches,
+            games: Mutex::new(Games::default()),
+            stats_running: Mutex::new(()),
+        }
+    }
+
+    /// Serves requests on localhost until the process is stopped. Each request
+    /// is handled on its own thread, so a long stats run doesn't hold up games.
+    pub fn serve(self, port: u16) -> Result<(), HmodeError> {
+        let server = tiny_http::Server::http(("127.0.0.1", port)).map_err(|e| {
+            HmodeError::io(
+                format!("listening on port {port}"),
+                std::io::Error::other(e),
+            )
+        })?;
+        eprintln!("Listening on http://127.0.0.1:{port}");
+        let api = Arc::new(self);
+        for request in server.incoming_requests() {
+            let api = api.clone();
+            std::thread::spawn(move || api.respond(request));
+        }
+        Ok(())
+    }
+
+    fn respond(&self, mut request: Request) {
+        let mut body = String::new();
+        let (status, value) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => self.handle(request.method().as_str(), request.url(), &body),
+            Err(e) => error_response(HmodeError::io("reading request", e)),
+        };
+        let header = Header::from_bytes("Content-Type", "application/json")
+            .expect("The content type header is valid");
+        // The client may have gone away, which only matters to the client
+        _ = request.respond(
+            Response::from_string(value.to_string())
+                .with_status_code(status)
+                .with_header(header),
+        );
+    }
+
+    /// Handles a request, returning the HTTP status and the JSON response.
+    pub fn handle(&self, method: &str, url: &str, body: &str) -> (u16, Value) {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let result = match (method, segments.as_slice()) {
+            ("POST", ["games"]) => self.new_game(body),
+            ("GET", ["games", id]) => self.game(id),
+            ("POST", ["games", id, "guesses"]) => self.guess(id, body),
+            ("POST", ["suggestions"]) => self.suggestions(body),
+            ("GET", ["stats"]) => self.stats(query),
+            _ => Ok((
+                404,
+                json!({"error": format!("Unknown endpoint {method} {path}")}),
+            )),
+        };
+        result.unwrap_or_else(error_response)
+    }
+
+    fn new_game(&self, body: &str) -> ApiResult {
+        let body = parse_body(body)?;
+        let setter = match body.get("seed") {
+            Some(seed) => Setter::from_seed(seed.as_u64().ok_or_else(|| {
+                HmodeError::InvalidArgument("The seed must be a positive integer".into())
+            })?),
+            None => Setter::new(),
+        };
+        let mut games = self.games.lock().unwrap();
+        if games.games.len() >= MAX_GAMES {
+            games.games.pop_first();
+        }
+        games.next_id += 1;
+        let id = games.next_id;
+        games.games.insert(
+            id,
+            Game {
+                setter,
+                history: Vec::new(),
+            },
+        );
+        Ok((201, json!({"id": id})))
+    }
+
+    fn game(&self, id: &str) -> ApiResult {
+        let games = self.games.lock().unwrap();
+        let Some(game) = parse_id(id).and_then(|id| games.games.get(&id)) else {
+            return Ok(unknown_game(id));
+        };
+        let mut response = game.state();
+        response["id"] = json!(parse_id(id));
+        response["guesses"] = json!(game.history.iter().map(clue_json).collect::<Vec<_>>());
+        Ok((200, response))
+    }
+
+    fn guess(&self, id: &str, body: &str) -> ApiResult {
+        let guess = word_field(&parse_body(body)?, "guess", self.alt_words)?;
+        let mut games = self.games.lock().unwrap();
+        let Some(game) = parse_id(id).and_then(|id| games.games.get_mut(&id)) else {
+            return Ok(unknown_game(id));
+        };
+        if game.solved() || game.lost() {
+            return Err(HmodeError::InvalidArgument("The game is over".into()));
+        }
+        self.rules.check(&game.history, guess)?;
+        let result = game.setter.check(guess);
+        game.history.push(result);
+        let mut response = game.state();
+        response["guess"] = json!(word_str(&guess));
+        response["pattern"] = json!(pattern_string(&result));
+        Ok((200, response))
+    }
+
+    fn suggestions(&self, body: &str) -> ApiResult {
+        let body = parse_body(body)?;
+        let count = match body.get("count") {
+            Some(count) => count.as_u64().ok_or_else(|| {
+                HmodeError::InvalidArgument("The count must be a positive integer".into())
+            })? as usize,
+            None => DEFAULT_SUGGESTIONS,
+        };
+        let mut solver = self.caches.solver(self.alt_words);
+        let mut history = Vec::new();
+        for entry in body
+            .get("history")
+            .and_then(Value::as_array)
+            .map_or(&[][..], Vec::as_slice)
+        {
+            let guess = word_field(entry, "guess", self.alt_words)?;
+            let pattern = entry.get("pattern").and_then(Value::as_str).unwrap_or("");
+            let result = parse_pattern(guess, pattern)?;
+            self.rules.check(&history, guess)?;
+            history.push(result);
+            solver.record_guess(guess, result);
+        }
+        let suggestions = solver.suggestions(count)?;
+        Ok((
+            200,
+            json!({
+                "remaining": solver.remaining(),
+                "suggestions": suggestions.iter().map(word_str).collect::<Vec<_>>(),
+            }),
+        ))
+    }
+
+    fn stats(&self, query: &str) -> ApiResult {
+        let mut start_word = DEFAULT_START_WORD;
+        let mut shard = None;
+        for (key, value) in query.split('&').filter_map(|p| p.split_once('=')) {
+            match key {
+                "start_word" => start_word = to_static_word(value, false, self.alt_words)?,
+                "shard" => shard = Some(value.parse::<Shard>()?),
+                _ => {}
+            }
+        }
+        let Ok(_running) = self.stats_running.try_lock() else {
+            return Ok((
+                503,
+                json!({"error": "Stats are already being calculated. Try again later"}),
+            ));
+        };
+        let solves = solve_answers(word_str(&start_word), self.alt_words, shard, &self.caches)?;
+        let mut stats = Stats::from_solves(&solves).to_json();
+        stats["start_word"] = json!(word_str(&start_word));
+        stats["answers"] = json!(solves.len());
+        Ok((200, stats))
+    }
+}
+
+fn clue_json(result: &CheckResult) -> Value {
+    json!({
+        "guess": word_str(&guessed_word(result)),
+        "pattern": pattern_string(result),
+    })
+}
+
+fn parse_id(id: &str) -> Option<u64> {
+    id.parse().ok()
+}
+
+fn unknown_game(id: &str) -> (u16, Value) {
+    (404, json!({"error": format!("Unknown game '{id}'")}))
+}
+
+// An empty body is treated as an empty object
+fn parse_body(body: &str) -> Result<Value, HmodeError> {
+    if body.trim().is_empty() {
+        return Ok(json!({}));
+    }
+    serde_json::from_str(body)
+        .map_err(|e| HmodeError::InvalidArgument(format!("Invalid JSON request, {e}")))
+}
+
+fn word_field(value: &Value, field: &str, alt_words: bool) -> Result<WdlWord, HmodeError> {
+    let word = value
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| HmodeError::InvalidArgument(format!("Missing '{field}' word")))?;
+    to_static_word(word, false, alt_words)
+}
+
+fn error_response(e: HmodeError) -> (u16, Value) {
+    let status = match e {
+        HmodeError::Io { .. } => 500,
+        HmodeError::Interrupted(_) => 503,
+        _ => 400,
+    };
+    (status, json!({"error": e.to_string()}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_with_rules(rules: RuleSet) -> Api {
+        Api::new(false, rules, SolverCaches::new(false))
+    }
+
+    fn api() -> Api {
+        api_with_rules(RuleSet::Hard)
+    }
+
+    #[test]
+    fn play_a_game() {
+        let api = api();
+        let (status, game) = api.handle("POST", "/games", r#"{"seed": 1}"#);
+        assert_eq!(status, 201);
+        let id = game["id"].as_u64().unwrap();
+        let answer = word_str(&Setter::from_seed(1).word()).to_string();
+        let url = format!("/games/{id}/guesses");
+
+        let (status, response) = api.handle("POST", &url, r#"{"guess": "tares"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(response["solved"], false);
+        assert_eq!(response["pattern"].as_str().unwrap().len(), 5);
+        let (status, response) = api.handle("POST", &url, r#"{"guess": "xxxxx"}"#);
+        assert_eq!(status, 400);
+        assert!(
+            response["error"]
+                .as_str()
+                .unwrap()
+                .contains("not in the list")
+        );
+        let (_, response) = api.handle("POST", &url, &json!({"guess": answer}).to_string());
+        assert_eq!(response["solved"], true);
+
+        let (status, response) = api.handle("GET", &format!("/games/{id}"), "");
+        assert_eq!(status, 200);
+        assert_eq!(response["guesses"][1]["guess"], answer.as_str());
+        assert_eq!(api.handle("GET", "/games/99", "").0, 404);
+        assert_eq!(api.handle("DELETE", "/games", "").0, 404);
+    }
+
+    #[test]
+    fn game_is_lost_after_six_guesses() {
+        let api = api_with_rules(RuleSet::Normal);
+        let (_, game) = api.handle("POST", "/games", r#"{"seed": 1}"#);
+        let url = format!("/games/{}/guesses", game["id"]);
+        let answer = word_str(&Setter::from_seed(1).word()).to_string();
+        let wrong = [
+            "crane", "pilot", "dummy", "fjord", "glyph", "vouch", "bumpy",
+        ];
+        let mut wrong = wrong.iter().filter(|&&w| w != answer);
+        for _ in 0..MAX_GUESSES {
+            let body = json!({"guess": wrong.next().unwrap()}).to_string();
+            let (status, response) = api.handle("POST", &url, &body);
+            assert_eq!(status, 200);
+            assert_eq!(response["solved"], false);
+        }
+        let (_, response) = api.handle("GET", &format!("/games/{}", game["id"]), "");
+        assert_eq!(response["lost"], true);
+        assert_eq!(response["answer"], answer.as_str());
+        let (status, response) = api.handle("POST", &url, &json!({"guess": answer}).to_string());
+        assert_eq!(status, 400);
+        assert_eq!(response["error"], "The game is over");
+    }
+
+    #[test]
+    fn suggestions_follow_history() {
+        let api = api();
+        let (status, response) = api.handle("POST", "/suggestions", "");
+        assert_eq!(status, 200);
+        assert_eq!(response["suggestions"], json!(["tares"]));
+        let history = json!({
+            "history": [{"guess": "tares", "pattern": "--Y--"}],
+            "count": 2,
+        });
+        let (status, response) = api.handle("POST", "/suggestions", &history.to_string());
+        assert_eq!(status, 200);
+        assert_eq!(response["suggestions"].as_array().unwrap().len(), 2);
+        let history = json!({"history": [{"guess": "tares", "pattern": "GGGGY"}]});
+        let (status, response) = api.handle("POST", "/suggestions", &history.to_string());
+        assert_eq!(status, 400);
+        assert!(response["error"].as_str().unwrap().contains("GGGGY"));
+    }
+
+    #[test]
+    fn stats_for_a_shard() {
+        let (status, response) = api().handle("GET", "/stats?start_word=tares&shard=1/500", "");
+        assert_eq!(status, 200);
+        assert_eq!(response["start_word"], "tares");
+        assert_eq!(response["answers"], 5);
+        assert_eq!(api().handle("GET", "/stats?shard=0/5", "").0, 400);
+
+        let api = api();
+        let _running = api.stats_running.lock().unwrap();
+        assert_eq!(api.handle("GET", "/stats?shard=1/500", "").0, 503);
+    }
+}
@@ -1,39 +1,263 @@
+use crate::error::HmodeError;
 use crate::words::{answers, to_static_word};
-use rand::distributions::{Distribution, Uniform};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+// Fills the start of the generator seed for daily puzzles
+const DAILY_SEED_TAG: &[u8] = b"hmode daily puzzle";
+
+/// The clue for one letter of a guess, holding the guessed letter.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum Clue {
+    /// The letter is not in the answer, or not as many times as guessed
     Wrong(u8),
+    /// The letter is in the answer, but in a different place
     Elsewhere(u8),
+    /// The letter is in the right place
     Right(u8),
 }
 
+/// The clues for a whole guess.
 pub type CheckResult = [Clue; 5];
 
+/// The number of guesses a player has to find the answer.
+pub const MAX_GUESSES: usize = 6;
+
+/// Returns true if every letter in the result is in the right place.
+pub fn is_solved(result: &CheckResult) -> bool {
+    result.iter().all(|c| matches!(c, Clue::Right(_)))
+}
+
+/// Formats a CheckResult as a compact pattern, using 'G' for a letter in the
+/// right place, 'Y' for a letter elsewhere in the word and '-' for a letter
+/// that is not in the word.
+pub fn pattern_string(result: &CheckResult) -> String {
+    result
+        .iter()
+        .map(|c| match c {
+            Clue::Right(_) => 'G',
+            Clue::Elsewhere(_) => 'Y',
+            Clue::Wrong(_) => '-',
+        })
+        .collect()
+}
+
+/// Returns the guess that a CheckResult was given for.
+pub fn guessed_word(result: &CheckResult) -> [u8; 5] {
+    result.map(|c| match c {
+        Clue::Right(c) | Clue::Elsewhere(c) | Clue::Wrong(c) => c,
+    })
+}
+
+/// The number of distinct clue patterns for a guess.
+pub const PATTERN_COUNT: usize = 243;
+
+/// Encodes the pattern of a CheckResult as a number from 0 to 242, treating
+/// each clue as a base 3 digit.
+pub fn pattern_index(result: &CheckResult) -> usize {
+    result.iter().rev().fold(0, |index, clue| {
+        index * 3
+            + match clue {
+                Clue::Wrong(_) => 0,
+                Clue::Elsewhere(_) => 1,
+                Clue::Right(_) => 2,
+            }
+    })
+}
+
+/// Rebuilds the CheckResult for a guess from its pattern index.
+pub fn pattern_from_index(guess: [u8; 5], index: usize) -> CheckResult {
+    let mut result = [Clue::Wrong(0); 5];
+    let mut rest = index;
+    for (i, clue) in result.iter_mut().enumerate() {
+        *clue = match rest % 3 {
+            0 => Clue::Wrong(guess[i]),
+            1 => Clue::Elsewhere(guess[i]),
+            _ => Clue::Right(guess[i]),
+        };
+        rest /= 3;
+    }
+    result
+}
+
+/// Parses a pattern in the form produced by `pattern_string` into the
+/// CheckResult it describes for the given guess. Letters are not case-sensitive.
+pub fn parse_pattern(guess: [u8; 5], pattern: &str) -> Result<CheckResult, HmodeError> {
+    let pattern = pattern.trim();
+    if pattern.chars().count() != 5 {
+        return Err(HmodeError::InvalidPattern(pattern.into()));
+    }
+    let mut result = [Clue::Wrong(0); 5];
+    for (i, c) in pattern.chars().enumerate() {
+        result[i] = match c.to_ascii_uppercase() {
+            'G' => Clue::Right(guess[i]),
+            'Y' => Clue::Elsewhere(guess[i]),
+            '-' => Clue::Wrong(guess[i]),
+            _ => return Err(HmodeError::InvalidPattern(pattern.into())),
+        };
+    }
+    Ok(result)
+}
+
+fn ordinal(n: usize) -> &'static str {
+    ["1st", "2nd", "3rd", "4th", "5th"][n]
+}
+
+/// Checks a guess against the clues for earlier guesses using Wordle's hard
+/// mode rules, where letters in the right place must be used in the same place
+/// and letters found elsewhere must be included in the guess. Returns a
+/// description of the first rule that the guess breaks, if any.
+pub fn hard_mode_violation(previous: &[CheckResult], guess: [u8; 5]) -> Option<String> {
+    for result in previous {
+        for (i, clue) in result.iter().enumerate() {
+            if let Clue::Right(c) = *clue
+                && guess[i] != c
+            {
+                return Some(format!(
+                    "{} letter must be {}",
+                    ordinal(i),
+                    (c as char).to_ascii_uppercase()
+                ));
+            }
+        }
+        for clue in result {
+            if let Clue::Elsewhere(c) = *clue {
+                // Repeated letters must appear at least as many times as they were found
+                let found = result
+                    .iter()
+                    .filter(|r| matches!(r, Clue::Right(x) | Clue::Elsewhere(x) if *x == c))
+                    .count();
+                if guess.iter().filter(|&&g| g == c).count() < found {
+                    return Some(format!(
+                        "Guess must contain {}",
+                        (c as char).to_ascii_uppercase()
+                    ));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The rules that a player's guesses must follow.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub enum RuleSet {
+    /// Each guess must use the letters revealed by earlier clues
+    #[default]
+    Hard,
+    /// Any valid word may be guessed at any time
+    Normal,
+}
+
+impl RuleSet {
+    /// Returns a description of the first rule that the guess breaks, given the
+    /// clues for earlier guesses, if any.
+    pub fn violation(&self, previous: &[CheckResult], guess: [u8; 5]) -> Option<String> {
+        match self {
+            RuleSet::Hard => hard_mode_violation(previous, guess),
+            RuleSet::Normal => None,
+        }
+    }
+
+    /// Checks that a guess follows the rules, given the clues for earlier guesses.
+    pub fn check(&self, previous: &[CheckResult], guess: [u8; 5]) -> Result<(), HmodeError> {
+        match self.violation(previous, guess) {
+            Some(rule) => Err(HmodeError::HardModeViolation(rule)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::str::FromStr for RuleSet {
+    type Err = HmodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "hard" => Ok(RuleSet::Hard),
+            "normal" => Ok(RuleSet::Normal),
+            _ => Err(HmodeError::InvalidArgument(format!(
+                "Unknown rule set '{s}'. Use 'hard' or 'normal'"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RuleSet::Hard => write!(f, "hard"),
+            RuleSet::Normal => write!(f, "normal"),
+        }
+    }
+}
+
+/// Knows the answer word, and gives the clues for each guess.
 #[derive(Debug)]
 pub struct Setter {
     chosen: [u8; 5],
 }
 
+#[cfg(feature = "thread-rng")]
+impl Default for Setter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Creates a setter for the given answer word.
+impl std::str::FromStr for Setter {
+    type Err = HmodeError;
+
+    fn from_str(word: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from_word(to_static_word(word, true, false)?))
+    }
+}
+
 impl Setter {
-    #[allow(dead_code)]
+    /// Chooses a random answer word.
+    #[cfg(feature = "thread-rng")]
     pub fn new() -> Self {
+        Self::from_rng(&mut rand::thread_rng())
+    }
+
+    /// Chooses an answer word using the given random number generator. The
+    /// index is sampled as a u32 rather than a usize, whose sampling depends
+    /// on the pointer width, so that seeded words are the same on 32 and 64
+    /// bit targets.
+    pub fn from_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
         let w = answers();
-        let range = Uniform::new(0usize, w.len());
-        let mut rng = rand::thread_rng();
-        let index = range.sample(&mut rng);
-        Self::from_word(w[index])
+        let index = rng.gen_range(0..w.len() as u32);
+        Self::from_word(w[index as usize])
+    }
+
+    /// Chooses an answer word from a seeded generator, so that the same seed
+    /// always gives the same word on every platform.
+    pub fn from_seed(seed: u64) -> Self {
+        Self::from_rng(&mut ChaCha8Rng::seed_from_u64(seed))
     }
 
+    /// Chooses the answer word for a given day, expressed as the number of
+    /// days since 1970-01-01. Everyone playing on the same day gets the same word.
+    /// The generator is seeded with a tag as well as the day, so that a day's
+    /// puzzle isn't the one given by its day number as a seed.
+    pub fn daily(day: i64) -> Self {
+        let mut seed = [0; 32];
+        seed[..DAILY_SEED_TAG.len()].copy_from_slice(DAILY_SEED_TAG);
+        seed[24..].copy_from_slice(&day.to_le_bytes());
+        Self::from_rng(&mut ChaCha8Rng::from_seed(seed))
+    }
+
+    /// Creates a setter for a word known to be in the word list.
     pub fn from_word(word: [u8; 5]) -> Self {
         Setter { chosen: word }
     }
 
-    pub fn from_str(word: &str) -> Result<Self, String> {
-        let w = to_static_word(word, true, false)?;
-        Ok(Self::from_word(w))
+    /// The answer word.
+    pub fn word(&self) -> [u8; 5] {
+        self.chosen
     }
 
+    /// Returns the clues for a guess, following Wordle's rules for repeated letters.
     pub fn check(&self, word: [u8; 5]) -> CheckResult {
         let mut chosen_copy = self.chosen;
         let mut word_copy = word;
@@ -76,6 +300,84 @@ impl Setter {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::words::all;
+    use proptest::prelude::*;
+
+    // Wordle scoring written for clarity rather than speed, to check
+    // Setter::check against. Exact matches are found first; then each other
+    // guess letter, from left to right, is Elsewhere while the answer still has
+    // copies of that letter that haven't been matched.
+    fn reference_check(answer: [u8; 5], guess: [u8; 5]) -> CheckResult {
+        let mut unmatched = [0; 256];
+        for i in 0..5 {
+            if answer[i] != guess[i] {
+                unmatched[answer[i] as usize] += 1;
+            }
+        }
+        let mut result = guess.map(Clue::Wrong);
+        for i in 0..5 {
+            let c = guess[i];
+            if answer[i] == c {
+                result[i] = Clue::Right(c);
+            } else if unmatched[c as usize] > 0 {
+                unmatched[c as usize] -= 1;
+                result[i] = Clue::Elsewhere(c);
+            }
+        }
+        result
+    }
+
+    // Answer, guess and expected pattern for duplicate letters
+    const DUPLICATE_LETTER_CASES: &[(&[u8; 5], &[u8; 5], &str)] = &[
+        (b"abbey", b"babes", "YYGG-"),
+        (b"crook", b"ooooo", "--GG-"),
+        (b"sassy", b"essay", "-YGYG"),
+        (b"ahead", b"aaaaa", "G--G-"),
+        (b"hello", b"lolly", "-YGG-"),
+        (b"llama", b"hello", "--YY-"),
+        (b"abide", b"speed", "--Y-Y"),
+        (b"those", b"geese", "---GG"),
+        (b"eerie", b"elder", "G--YY"),
+        (b"maybe", b"cable", "-GY-G"),
+        (b"abccd", b"fccgh", "-YG--"),
+    ];
+
+    #[test]
+    fn duplicate_letter_corpus() {
+        for (answer, guess, pattern) in DUPLICATE_LETTER_CASES {
+            let result = Setter::from_word(**answer).check(**guess);
+            assert_eq!(
+                pattern_string(&result),
+                *pattern,
+                "answer {}, guess {}",
+                String::from_utf8_lossy(*answer),
+                String::from_utf8_lossy(*guess)
+            );
+            assert_eq!(result, reference_check(**answer, **guess));
+        }
+    }
+
+    proptest! {
+        // A small alphabet gives plenty of repeated letters
+        #[test]
+        fn check_matches_reference(
+            answer in proptest::array::uniform5(b'a'..=b'd'),
+            guess in proptest::array::uniform5(b'a'..=b'd'),
+        ) {
+            prop_assert_eq!(Setter::from_word(answer).check(guess), reference_check(answer, guess));
+        }
+    }
+
+    #[test]
+    #[ignore] // Checks every answer against every probe word, which is slow in a debug build
+    fn check_matches_reference_for_all_words() {
+        for &answer in answers() {
+            let setter = Setter::from_word(answer);
+            for &guess in all(false) {
+                assert_eq!(setter.check(guess), reference_check(answer, guess));
+            }
+        }
+    }
 
     fn mock_setter() -> Setter {
         Setter::from_word(*b"abcce")
@@ -97,6 +399,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::explicit_counter_loop)]
     fn check_no_match() {
         let test = *b"fghij";
         let result = mock_setter().check(test);
@@ -182,6 +485,94 @@ mod test {
         );
     }
 
+    #[test]
+    fn seeded_setter_is_deterministic() {
+        assert_eq!(Setter::from_seed(42).word(), Setter::from_seed(42).word());
+        assert_eq!(Setter::daily(19_000).word(), Setter::daily(19_000).word());
+        // Days and seeds give different puzzles, apart from chance matches
+        let same = (0..100)
+            .filter(|&day| Setter::daily(day).word() == Setter::from_seed(day as u64).word())
+            .count();
+        assert!(same < 3);
+    }
+
+    #[test]
+    fn seeded_words_are_pinned() {
+        // These words must not change, on any target, as players share seeds
+        // and everyone gets the same daily puzzle
+        assert_eq!(&Setter::from_seed(42).word(), b"crier");
+        assert_eq!(&Setter::from_seed(0).word(), b"morph");
+        assert_eq!(&Setter::daily(20_000).word(), b"mange");
+    }
+
+    #[test]
+    fn seeded_setter_chooses_answer_words() {
+        for seed in 0..100 {
+            assert!(answers().contains(&Setter::from_seed(seed).word()));
+        }
+    }
+
+    #[test]
+    fn pattern_string_shows_each_clue() {
+        let result = Setter::from_word(*b"maybe").check(*b"cable");
+        assert_eq!(pattern_string(&result), "-GY-G");
+        assert!(!is_solved(&result));
+        assert!(is_solved(&Setter::from_word(*b"maybe").check(*b"maybe")));
+    }
+
+    #[test]
+    fn parse_pattern_round_trips() {
+        let result = Setter::from_word(*b"maybe").check(*b"cable");
+        assert_eq!(parse_pattern(*b"cable", "-gY-G"), Ok(result));
+        assert!(parse_pattern(*b"cable", "-GY-").is_err());
+        assert!(parse_pattern(*b"cable", "-GYXG").is_err());
+    }
+
+    #[test]
+    fn hard_mode_requires_known_letters() {
+        let setter = Setter::from_word(*b"maybe");
+        let previous = [setter.check(*b"cable")];
+        assert_eq!(hard_mode_violation(&previous, *b"mabse"), None);
+        assert_eq!(
+            hard_mode_violation(&previous, *b"gasse"),
+            Some("Guess must contain B".into())
+        );
+        assert_eq!(
+            hard_mode_violation(&previous, *b"mbyse"),
+            Some("2nd letter must be A".into())
+        );
+    }
+
+    #[test]
+    fn hard_mode_counts_repeated_letters() {
+        let previous = [Setter::from_word(*b"sassy").check(*b"essay")];
+        assert!(hard_mode_violation(&previous, *b"sassy").is_none());
+        assert!(hard_mode_violation(&previous, *b"asset").is_some());
+    }
+
+    #[test]
+    fn normal_rules_allow_any_guess() {
+        let previous = [Setter::from_word(*b"maybe").check(*b"cable")];
+        assert!(RuleSet::Hard.violation(&previous, *b"gasse").is_some());
+        assert!(RuleSet::Normal.violation(&previous, *b"gasse").is_none());
+        assert_eq!("Normal".parse(), Ok(RuleSet::Normal));
+        assert!("easy".parse::<RuleSet>().is_err());
+    }
+
+    #[test]
+    fn pattern_index_round_trips() {
+        for index in 0..PATTERN_COUNT {
+            let result = pattern_from_index(*b"tares", index);
+            assert_eq!(pattern_index(&result), index);
+        }
+        let result = Setter::from_word(*b"maybe").check(*b"cable");
+        assert_eq!(
+            pattern_from_index(*b"cable", pattern_index(&result)),
+            result
+        );
+        assert_eq!(guessed_word(&result), *b"cable");
+    }
+
     #[test]
     fn real_world() {
         let result = Setter::from_word(*b"maybe").check(*b"cable");
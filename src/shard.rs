@@ -0,0 +1,139 @@
+use crate::error::HmodeError;
+
+/// One of `count` disjoint slices of a word list, numbered from 1, so that a
+/// long run can be split across several processes. Words are dealt to shards
+/// in turn, which keeps the slices the same size.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Shard {
+    index: usize,
+    count: usize,
+}
+
+impl Shard {
+    pub fn new(index: usize, count: usize) -> Result<Self, HmodeError> {
+        if count == 0 || index == 0 || index > count {
+            return Err(HmodeError::InvalidArgument(format!(
+                "Invalid shard {index}/{count}. Shards are numbered from 1 to the shard count"
+            )));
+        }
+        Ok(Shard { index, count })
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if the item at the given position in a list belongs to this shard.
+    pub fn contains(&self, position: usize) -> bool {
+        position % self.count == self.index - 1
+    }
+
+    /// Returns the items of a list that belong to this shard.
+    pub fn select<'a, T>(&self, list: &'a [T]) -> impl Iterator<Item = &'a T> + use<'a, T> {
+        let shard = *self;
+        list.iter()
+            .enumerate()
+            .filter(move |(i, _)| shard.contains(*i))
+            .map(|(_, item)| item)
+    }
+}
+
+/// Returns the items of a list that belong to the shard, or every item if there is no shard.
+pub fn select<T>(shard: Option<Shard>, list: &[T]) -> Vec<&T> {
+    match shard {
+        Some(shard) => shard.select(list).collect(),
+        None => list.iter().collect(),
+    }
+}
+
+impl std::str::FromStr for Shard {
+    type Err = HmodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            HmodeError::InvalidArgument(format!("Invalid shard '{s}'. Use the form i/n, e.g. 2/4"))
+        };
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        Shard::new(
+            index.trim().parse().map_err(|_| invalid())?,
+            count.trim().parse().map_err(|_| invalid())?,
+        )
+    }
+}
+
+impl std::fmt::Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// Checks that a set of shards covers a whole list exactly once, and returns a
+/// description of the problem if not.
+pub fn check_complete(shards: &[Option<Shard>]) -> Option<String> {
+    if shards.iter().all(Option::is_none) {
+        return (shards.len() > 1).then(|| "Merging several unsharded result files".into());
+    }
+    let count = shards.iter().flatten().map(Shard::count).next()?;
+    if shards.iter().any(|s| s.is_none_or(|s| s.count() != count)) {
+        return Some("The result files come from runs with different shard counts".into());
+    }
+    let missing: Vec<String> = (1..=count)
+        .filter(|&i| !shards.iter().flatten().any(|s| s.index() == i))
+        .map(|i| format!("{i}/{count}"))
+        .collect();
+    if missing.len() > 10 {
+        return Some(format!(
+            "Missing results for {} shards, including {}",
+            missing.len(),
+            missing[..10].join(", ")
+        ));
+    } else if !missing.is_empty() {
+        return Some(format!("Missing results for shards {}", missing.join(", ")));
+    }
+    (shards.len() > count).then(|| "Some shards appear more than once".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shards_are_disjoint_and_complete() {
+        let list: Vec<usize> = (0..10).collect();
+        let shards: Vec<Shard> = (1..=3).map(|i| Shard::new(i, 3).unwrap()).collect();
+        let mut all: Vec<usize> = shards
+            .iter()
+            .flat_map(|s| s.select(&list))
+            .copied()
+            .collect();
+        all.sort();
+        assert_eq!(all, list);
+        assert_eq!(
+            shards[1].select(&list).copied().collect::<Vec<_>>(),
+            [1, 4, 7]
+        );
+    }
+
+    #[test]
+    fn parse_shard() {
+        assert_eq!("2/4".parse(), Shard::new(2, 4));
+        assert!("0/4".parse::<Shard>().is_err());
+        assert!("5/4".parse::<Shard>().is_err());
+        assert!("2-4".parse::<Shard>().is_err());
+        assert_eq!(Shard::new(2, 4).unwrap().to_string(), "2/4");
+    }
+
+    #[test]
+    fn check_complete_finds_missing_shards() {
+        let shard = |i| Some(Shard::new(i, 3).unwrap());
+        assert_eq!(check_complete(&[shard(1), shard(2), shard(3)]), None);
+        assert!(check_complete(&[shard(1), shard(3)]).is_some());
+        assert!(check_complete(&[shard(1), shard(2), shard(3), shard(3)]).is_some());
+        assert!(check_complete(&[shard(1), Some(Shard::new(2, 4).unwrap())]).is_some());
+        assert_eq!(check_complete(&[None]), None);
+    }
+}
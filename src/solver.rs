@@ -1,40 +1,100 @@
-use crate::setter::{CheckResult, Clue, Setter};
-use crate::words::{all, answers, to_static_word, DEFAULT_START_WORD};
+use crate::decision_cache::DecisionCache;
+use crate::error::HmodeError;
+use crate::second_guess::SecondGuessTable;
+use crate::setter::{self, CheckResult, Clue, Setter};
+use crate::words::{all, answers, to_static_word, WdlWord, DEFAULT_START_WORD};
+use std::sync::Arc;
 
+/// Finds an answer word by choosing guesses and narrowing down the possible
+/// answers using the clues they receive. Every guess follows the hard mode rules.
+///
+/// The remaining answer and probe words are held as indices into the static
+/// word list, so a solver is cheap to clone, e.g. to try each possible clue
+/// from the same position.
 #[derive(Debug, Clone)]
 pub struct Solver {
-    words: Vec<[u8; 5]>,
+    list: &'static [WdlWord],
+    words: Vec<u16>,
     start_word: [u8; 5],
-    probe_words: Vec<[u8; 5]>,
+    probe_words: Vec<u16>,
     guesses: u32,
     use_alt_words: bool,
+    history: Vec<CheckResult>,
+    // The clues that eliminated the last possible answer, if any
+    contradiction: Option<CheckResult>,
+    second_guesses: Option<Arc<SecondGuessTable>>,
+    decisions: Option<Arc<DecisionCache>>,
 }
 
+/// Names the algorithm used to choose guesses, so that saved decisions can be
+/// discarded if it changes.
+pub const STRATEGY: &str = "max-reduction";
+
 impl Solver {
+    /// Creates a solver that guesses from the full Wordle word list, or from the
+    /// shorter alternative list.
     pub fn new(alt_words: bool) -> Self {
+        // The answer words come first in both word lists
+        let list = all(alt_words);
         Solver {
-            words: answers().to_vec(),
+            list,
+            words: (0..answers().len() as u16).collect(),
             start_word: DEFAULT_START_WORD,
-            probe_words: all(alt_words).to_vec(),
+            probe_words: (0..list.len() as u16).collect(),
             guesses: 0,
             use_alt_words: alt_words,
+            history: Vec::new(),
+            contradiction: None,
+            second_guesses: None,
+            decisions: None,
         }
     }
 
-    pub fn with_start_word(mut self, word: &str) -> Result<Self, String> {
+    /// Uses the given word as the first guess, instead of the default start word.
+    pub fn with_start_word(mut self, word: &str) -> Result<Self, HmodeError> {
         self.start_word = to_static_word(word, false, self.use_alt_words)?;
         Ok(self)
     }
 
+    /// Looks up the second guess in a precomputed table instead of searching
+    /// for it. The table is only used if it was built for the same start word
+    /// and word list.
+    pub fn with_second_guesses(mut self, table: Arc<SecondGuessTable>) -> Self {
+        self.second_guesses = Some(table);
+        self
+    }
+
+    /// Remembers each guess in a cache shared with other solvers, and reuses
+    /// the guesses they made in the same position. The cache is only used if it
+    /// was created for the same word list.
+    pub fn with_decision_cache(mut self, cache: Arc<DecisionCache>) -> Self {
+        if cache.alt_words() == self.use_alt_words {
+            self.decisions = Some(cache);
+        }
+        self
+    }
+
+    /// The first guess.
+    pub fn start_word(&self) -> WdlWord {
+        self.start_word
+    }
+
+    /// The number of guesses made so far.
     pub fn guesses(&self) -> u32 {
         self.guesses
     }
 
+    /// The number of answer words that match every clue so far.
     pub fn remaining(&self) -> usize {
         self.words.len()
     }
 
-    fn filter(list: &[[u8; 5]], clues: CheckResult) -> Vec<[u8; 5]> {
+    /// The answer words that match every clue so far.
+    pub fn candidates(&self) -> impl Iterator<Item = WdlWord> + '_ {
+        self.words.iter().map(|&id| self.list[id as usize])
+    }
+
+    fn filter(list: &[WdlWord], ids: &[u16], clues: CheckResult) -> Vec<u16> {
         let mut confirmed: [bool; 256] = [false; 256];
         for clue in &clues {
             match clue {
@@ -44,8 +104,9 @@ impl Solver {
         }
 
         // Apply position-specific filters to word list
-        let mut result: Vec<[u8; 5]> = Vec::with_capacity(list.len());
-        result.extend(list.iter().filter_map(|&word| {
+        let mut result: Vec<u16> = Vec::with_capacity(ids.len());
+        result.extend(ids.iter().filter(|&&id| {
+            let word = list[id as usize];
             for (i, clue) in clues.into_iter().enumerate() {
                 match clue {
                     Clue::Wrong(c) => {
@@ -54,28 +115,28 @@ impl Solver {
                         // or Elsewhere (orange) clue, so we can only eliminate words containing
                         // the letter if the letter doesn't exist elsewhere in the clues.
                         if word[i] == c || (word.contains(&c) && !confirmed[c as usize]) {
-                            return None;
+                            return false;
                         }
                     }
                     Clue::Right(c) => {
                         if word[i] != c {
-                            return None;
+                            return false;
                         }
                     }
                     Clue::Elsewhere(c) => {
                         if (!word.contains(&c)) || word[i] == c {
-                            return None;
+                            return false;
                         }
                     }
                 }
             }
-            Some(word)
+            true
         }));
         result
     }
 
     /// Count how many words in the list match the given clues (without allocating a filtered vector)
-    fn count_matching(list: &[[u8; 5]], clues: CheckResult) -> usize {
+    pub(crate) fn count_matching(list: &[WdlWord], clues: CheckResult) -> usize {
         let mut confirmed: [bool; 256] = [false; 256];
         for clue in &clues {
             match clue {
@@ -117,65 +178,166 @@ impl Solver {
         count
     }
 
+    /// Updates the solver with the clues for its latest guess.
     pub fn filter_self(&mut self, clues: CheckResult) {
-        self.words = Self::filter(&self.words, clues);
-        self.probe_words = Self::filter(&self.probe_words, clues);
+        self.history.push(clues);
+        let before = self.words.len();
+        self.words = Self::filter(self.list, &self.words, clues);
+        if before > 0 && self.words.is_empty() {
+            self.contradiction = Some(clues);
+        }
+        self.probe_words = Self::filter(self.list, &self.probe_words, clues);
     }
 
-    pub fn guess(&mut self) -> [u8; 5] {
-        // The exhaustive algorithm is too slow to select the first guess before the
-        // answer word list has been pruned, so we have a pre-selected starting word
-        if self.guesses == 0 {
-            self.guesses += 1;
-            return self.start_word;
-        }
-        assert!(!self.words.is_empty(), "Guess called with empty word list");
-        assert!(
-            !self.probe_words.is_empty(),
-            "Guess called with empty probe word list"
-        );
-        if self.words.len() == 1 {
-            return self.words[0];
+    // Removes a word from the probe list, as a guess should never be re-used
+    fn remove_probe(&mut self, guess: WdlWord) {
+        let list = self.list;
+        self.probe_words.retain(|&id| list[id as usize] != guess);
+    }
+
+    /// Updates the solver with a guess made elsewhere, e.g. by a human player,
+    /// and the clues it received, as though the solver had made the guess itself.
+    pub fn record_guess(&mut self, guess: [u8; 5], clues: CheckResult) {
+        self.guesses += 1;
+        self.remove_probe(guess);
+        self.filter_self(clues);
+    }
+
+    // Whether a word can still be guessed, so that a guess from a table or
+    // cache loaded from a file is never one that breaks the hard mode rules
+    fn is_probe(&self, word: WdlWord) -> bool {
+        self.probe_words
+            .iter()
+            .any(|&id| self.list[id as usize] == word)
+    }
+
+    fn table_second_guess(&self) -> Option<[u8; 5]> {
+        if self.guesses != 1 {
+            return None;
         }
-        let mut best_reduction = 0;
-        let mut best_word: Option<[u8; 5]> = None;
+        let table = self
+            .second_guesses
+            .as_ref()
+            .filter(|t| t.start_word == self.start_word && t.alt_words == self.use_alt_words)?;
+        let [first_clue] = self.history.as_slice() else {
+            return None;
+        };
+        table
+            .get(first_clue)
+            .filter(|entry| entry.remaining == self.words.len() && self.is_probe(entry.guess))
+            .map(|entry| entry.guess)
+    }
+
+    // Scores each probe word, in probe list order, by the total reduction in
+    // the number of possible answers over every remaining answer
+    fn probe_scores(&self) -> Vec<(WdlWord, usize)> {
         let start_len = self.words.len();
+        // Look the answer words up once, rather than for every probe
+        let words: Vec<WdlWord> = self.candidates().collect();
         
         // For each probe word, calculate total reduction in answer list size
-        for probe in &self.probe_words {
+        let mut scores = Vec::with_capacity(self.probe_words.len());
+        for &probe in &self.probe_words {
+            let probe = self.list[probe as usize];
             let mut total_diff = 0;
             
             // For each answer word, see how much this probe narrows down the list
-            for word in &self.words {
-                let setter = Setter::from_word(*word);
-                let clues = setter.check(*probe);
+            for &word in &words {
+                let setter = Setter::from_word(word);
+                let clues = setter.check(probe);
                 
                 // Count matching words without allocating a filtered vector
-                let matches = Solver::count_matching(&self.words, clues);
+                let matches = Solver::count_matching(&words, clues);
                 if matches > 0 {
                     let diff = start_len - matches;
                     total_diff += diff;
                 }
             }
-            
+            scores.push((probe, total_diff));
+        }
+        scores
+    }
+
+    /// Returns up to `n` guesses for the current position, best first, without
+    /// making a guess. The first is the guess that `guess` would make. Before
+    /// the first guess, only the start word is suggested, as searching every
+    /// word is too slow.
+    pub fn suggestions(&self, n: usize) -> Result<Vec<WdlWord>, HmodeError> {
+        self.check_clues()?;
+        if self.guesses == 0 {
+            return Ok(vec![self.start_word]);
+        }
+        if self.words.len() == 1 {
+            return Ok(self.candidates().collect());
+        }
+        let mut scores = self.probe_scores();
+        // The sort is stable, so the first of equally good words stays first,
+        // as in `guess`
+        scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        Ok(scores.into_iter().take(n).map(|(word, _)| word).collect())
+    }
+
+    /// Returns a `Contradiction` error, naming the clues that eliminated the
+    /// last possible answer, if no answer word matches all the clues so far.
+    pub fn check_clues(&self) -> Result<(), HmodeError> {
+        match self.contradiction {
+            Some(clues) => Err(HmodeError::Contradiction {
+                guess: String::from_utf8_lossy(&setter::guessed_word(&clues)).into_owned(),
+                pattern: setter::pattern_string(&clues),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Chooses the next guess, the word that is expected to leave the fewest
+    /// possible answers. Returns a `Contradiction` error if no answer word
+    /// matches all the clues so far.
+    pub fn guess(&mut self) -> Result<[u8; 5], HmodeError> {
+        self.check_clues()?;
+        // The exhaustive algorithm is too slow to select the first guess before the
+        // answer word list has been pruned, so we have a pre-selected starting word
+        if self.guesses == 0 {
+            self.guesses += 1;
+            return Ok(self.start_word);
+        }
+        if self.words.len() == 1 {
+            return Ok(self.list[self.words[0] as usize]);
+        }
+        if let Some(guess) = self.table_second_guess() {
+            self.guesses += 1;
+            self.remove_probe(guess);
+            return Ok(guess);
+        }
+        if let Some(guess) = self
+            .decisions
+            .as_ref()
+            .and_then(|cache| cache.get(self.start_word, &self.history))
+            .filter(|&guess| self.is_probe(guess))
+        {
+            self.guesses += 1;
+            self.remove_probe(guess);
+            return Ok(guess);
+        }
+        let mut best_reduction = 0;
+        let mut best_word: Option<[u8; 5]> = None;
+        for (probe, total_diff) in self.probe_scores() {
             if total_diff > best_reduction {
                 best_reduction = total_diff;
-                best_word = Some(*probe);
+                best_word = Some(probe);
             }
         }
 
         self.guesses += 1;
-        let result = best_word.unwrap_or_else(|| {
-            panic!(
-                "No probe word was selected. words : {:?}, probe_words : {:?}",
-                self.words, self.probe_words
-            )
-        });
-
-        // Remove the guess word from the probe_words list as we should never
-        // re-use a guess
-        self.probe_words.retain(|w| *w != result);
-        result
+        // If no probe word narrows down the answers, e.g. because the probe
+        // words have run out, any of the remaining answers is as good a guess
+        let result = best_word.unwrap_or(self.list[self.words[0] as usize]);
+
+        if let Some(cache) = &self.decisions {
+            cache.insert(self.start_word, &self.history, result);
+        }
+
+        self.remove_probe(result);
+        Ok(result)
     }
 }
 
@@ -183,12 +345,15 @@ impl Solver {
 mod tests {
 
     use super::*;
+    use crate::stats::SolverCaches;
 
     #[test]
+    #[allow(clippy::len_zero)]
     fn filter_handles_all_clues() {
         let original = Solver::new(false);
         let original_len = original.words.len();
         let filtered = Solver::filter(
+            original.list,
             &original.words,
             [
                 Clue::Right(b'a'),
@@ -201,7 +366,7 @@ mod tests {
         assert!(filtered.len() > 0);
         assert!(original_len > filtered.len());
 
-        for word in filtered {
+        for word in filtered.into_iter().map(|id| original.list[id as usize]) {
             for c in b"bcd" {
                 assert!(!word.contains(c));
             }
@@ -213,6 +378,7 @@ mod tests {
 
     #[test]
     #[ignore] // This test is very slow. To run, use 'cargo test --ignored' or 'cargo test --include-ignored'
+    #[allow(clippy::into_iter_on_ref)]
     fn test_some_words() {
         for &word in answers().into_iter().take(500) {
             println!("Testing : {}", std::str::from_utf8(&word).unwrap());
@@ -220,7 +386,7 @@ mod tests {
             let setter = Setter::from_word(word);
             let mut guess;
             loop {
-                guess = solver.guess();
+                guess = solver.guess().unwrap();
                 let result = setter.check(guess);
                 if let [Clue::Right(_), Clue::Right(_), Clue::Right(_), Clue::Right(_), Clue::Right(_)] =
                     result
@@ -234,18 +400,79 @@ mod tests {
         }
     }
 
+    // The guesses made for every answer with the default start word, one
+    // answer per line, so that any change to the solver's choices is noticed
+    const GOLDEN_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/solver-guesses.txt");
+
+    fn guess_sequences() -> String {
+        // Sharing decisions between games speeds the test up without changing
+        // them. No second guess table is used, not even one saved in the cache
+        // directory, so the guesses are the search's on every machine
+        let caches = SolverCaches::new(false);
+        let mut text = String::from(
+            "# hmode solver guesses for every answer, with the default start word\n\
+             # answer: guess...\n",
+        );
+        for &answer in answers() {
+            let mut solver = caches.solver(false);
+            let setter = Setter::from_word(answer);
+            text.push_str(&format!("{}:", String::from_utf8_lossy(&answer)));
+            loop {
+                let guess = solver.guess().unwrap();
+                text.push_str(&format!(" {}", String::from_utf8_lossy(&guess)));
+                let result = setter.check(guess);
+                if crate::setter::is_solved(&result) {
+                    break;
+                }
+                solver.filter_self(result);
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    // This test is slow. To run it, use 'cargo test --release -- --ignored golden'.
+    // After an intended change to the solver, re-bless the file by running the
+    // test with HMODE_BLESS=1 set, and review the changes to the file.
+    #[test]
+    #[ignore]
+    fn golden_guess_sequences() {
+        let actual = guess_sequences();
+        if std::env::var_os("HMODE_BLESS").is_some() {
+            std::fs::write(GOLDEN_FILE, &actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(GOLDEN_FILE).unwrap();
+        let changed: Vec<String> = expected
+            .lines()
+            .zip(actual.lines())
+            .filter(|(e, a)| e != a)
+            .map(|(e, a)| format!("  expected {e}\n  actual   {a}"))
+            .collect();
+        assert!(
+            changed.is_empty() && expected.lines().count() == actual.lines().count(),
+            "{} answers are solved differently. Re-bless with HMODE_BLESS=1 if this is intended.\n{}",
+            changed.len(),
+            changed[..changed.len().min(10)].join("\n")
+        );
+    }
+
     #[test]
     fn repeated_wrong_letter_does_not_eliminate_right_letters() {
         let setter = Setter::from_word(*b"crook");
         let mut solver = Solver::new(false);
         //        let mut guess = b"xxxxx";
         let (guess, clues) = loop {
-            let guess = solver.guess();
+            let guess = solver.guess().unwrap();
             let clues = setter.check(guess);
             {
                 solver.filter_self(clues);
             }
-            if !solver.probe_words.contains(&b"crook") {
+            if !solver
+                .probe_words
+                .iter()
+                .any(|&id| solver.list[id as usize] == *b"crook")
+            {
                 break (guess, clues);
             }
         };
@@ -262,6 +489,103 @@ mod tests {
         assert_eq!(guess, *b"crook");
     }
 
+    #[test]
+    fn record_guess_matches_own_guess() {
+        let setter = Setter::from_word(*b"crook");
+        let mut solver = Solver::new(false);
+        let mut other = solver.clone();
+        let guess = solver.guess().unwrap();
+        solver.filter_self(setter.check(guess));
+        other.record_guess(guess, setter.check(guess));
+        assert_eq!(solver.guesses(), other.guesses());
+        assert_eq!(solver.words, other.words);
+        assert_eq!(solver.guess(), other.guess());
+    }
+
+    #[test]
+    fn candidates_match_the_clues() {
+        let setter = Setter::from_word(*b"crook");
+        let mut solver = Solver::new(false);
+        let guess = solver.guess().unwrap();
+        solver.filter_self(setter.check(guess));
+        assert!(solver.candidates().any(|w| w == *b"crook"));
+        assert_eq!(solver.candidates().count(), solver.remaining());
+        assert!(
+            solver
+                .candidates()
+                .all(|w| Setter::from_word(w).check(guess) == setter.check(guess))
+        );
+    }
+
+    #[test]
+    fn decision_cache_is_shared() {
+        let cache = Arc::new(DecisionCache::new(false));
+        let setter = Setter::from_word(*b"crook");
+        let first_clue = setter.check(*b"tares");
+        let mut solver = Solver::new(false).with_decision_cache(cache.clone());
+        solver.record_guess(*b"tares", first_clue);
+        let guess = solver.guess().unwrap();
+        assert_eq!(cache.get(*b"tares", &[first_clue]), Some(guess));
+
+        // A planted decision shows that the cache is used instead of a search
+        cache.insert(*b"tares", &[first_clue], *b"grind");
+        let mut other = Solver::new(false).with_decision_cache(cache.clone());
+        other.record_guess(*b"tares", first_clue);
+        assert_eq!(other.guess(), Ok(*b"grind"));
+
+        // but not a decision that breaks the hard mode rules
+        cache.insert(*b"tares", &[first_clue], *b"pious");
+        let mut other = Solver::new(false).with_decision_cache(cache);
+        other.record_guess(*b"tares", first_clue);
+        assert_ne!(other.guess(), Ok(*b"pious"));
+    }
+
+    #[test]
+    fn table_guess_must_follow_the_clues() {
+        let first_clue = setter::parse_pattern(*b"tares", "--Y--").unwrap();
+        let mut solver = Solver::new(false);
+        solver.record_guess(*b"tares", first_clue);
+        let remaining = solver.remaining();
+        let table = |guess: &str| {
+            let text = format!(
+                "strategy {STRATEGY}\nalt-words false\nstart-word tares\npattern --Y-- {remaining} {guess}\n"
+            );
+            Arc::new(SecondGuessTable::parse(&text).unwrap())
+        };
+
+        // "pious" drops the R, so the solver searches instead
+        let mut planted = solver.clone().with_second_guesses(table("pious"));
+        assert_ne!(planted.guess(), Ok(*b"pious"));
+        let mut planted = solver.with_second_guesses(table("grind"));
+        assert_eq!(planted.guess(), Ok(*b"grind"));
+    }
+
+    #[test]
+    fn first_suggestion_is_the_guess() {
+        let setter = Setter::from_word(*b"crook");
+        let mut solver = Solver::new(false);
+        assert_eq!(solver.suggestions(3), Ok(vec![*b"tares"]));
+        let guess = solver.guess().unwrap();
+        solver.filter_self(setter.check(guess));
+        let suggestions = solver.suggestions(3).unwrap();
+        assert_eq!(suggestions.len(), 3);
+        assert_eq!(Ok(suggestions[0]), solver.guess());
+    }
+
+    #[test]
+    fn contradiction_names_the_eliminating_clue() {
+        let mut solver = Solver::new(false);
+        let guess = solver.guess().unwrap();
+        solver.record_guess(guess, setter::parse_pattern(guess, "GGGGY").unwrap());
+        assert_eq!(
+            solver.guess(),
+            Err(HmodeError::Contradiction {
+                guess: "tares".into(),
+                pattern: "GGGGY".into(),
+            })
+        );
+    }
+
     #[test]
     fn start_word() {
         let solver = Solver::new(false).with_start_word("winch").unwrap();
@@ -0,0 +1,304 @@
+use crate::checkpoint::{Checkpoint, StartWordRanking};
+use crate::decision_cache::DecisionCache;
+use crate::error::HmodeError;
+use crate::progress::{self, Progress};
+use crate::second_guess::{self, SecondGuessTable};
+use crate::setter::{Clue, Setter};
+use crate::shard::{self, Shard};
+use crate::solver::Solver;
+use crate::words;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// An answer word and the number of guesses the solver needed for it.
+#[derive(Debug, PartialEq)]
+pub struct Solve(pub String, pub u32);
+
+/// An answer that took more than 6 guesses to solve, with its number of guesses.
+pub struct Outlier(pub String, pub u32);
+
+/// The statistics of full games for a start word.
+pub struct Stats {
+    pub mean: f32,
+    pub max: u32,
+    pub outliers: Vec<Outlier>,
+}
+
+impl std::fmt::Display for Stats {
+    // This trait requires `fmt` with this exact signature.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // Write strictly the first element into the supplied output
+        // stream: `f`. Returns `fmt::Result` which indicates whether the
+        // operation succeeded or failed. Note that `write!` uses syntax which
+        // is very similar to `println!`.
+        write!(f, "\nAverage solving steps : {}\n", self.mean)?;
+        writeln!(f, "Maximum solving steps : {}", self.max)?;
+        if !self.outliers.is_empty() {
+            write!(f, "\nOutliers\n")?;
+            for outlier in &self.outliers {
+                writeln!(f, "{} : {}", outlier.0, outlier.1)?;
+            }
+        }
+        writeln!(f)
+    }
+}
+
+impl Stats {
+    pub fn from_solves(solves: &[Solve]) -> Self {
+        let total_guesses: u32 = solves.iter().map(|s| s.1).sum();
+        let outliers = solves
+            .iter()
+            .filter(|s| s.1 > 6)
+            .map(|s| Outlier(s.0.clone(), s.1))
+            .collect();
+        Stats {
+            mean: total_guesses as f32 / solves.len().max(1) as f32,
+            max: solves.iter().map(|s| s.1).max().unwrap_or(0),
+            outliers,
+        }
+    }
+
+    /// The number of answers that could not be solved within 6 guesses.
+    pub fn fails(&self) -> usize {
+        self.outliers.len()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "mean": self.mean,
+            "max": self.max,
+            "fails": self.fails(),
+            "outliers": self.outliers.iter().map(|o| json!({"word": o.0, "guesses": o.1})).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// The caches shared by all the solvers in a run.
+#[derive(Clone)]
+pub struct SolverCaches {
+    pub second_guesses: Option<Arc<SecondGuessTable>>,
+    /// Whether solvers whose start word doesn't match `second_guesses` use the
+    /// table that the second-guesses command saved in the cache directory
+    pub saved_second_guesses: bool,
+    pub decisions: Arc<DecisionCache>,
+}
+
+impl SolverCaches {
+    /// Empty caches for the word list, that don't use saved tables.
+    pub fn new(alt_words: bool) -> Self {
+        SolverCaches {
+            second_guesses: None,
+            saved_second_guesses: false,
+            decisions: Arc::new(DecisionCache::new(alt_words)),
+        }
+    }
+
+    /// Creates a solver that uses the caches.
+    pub fn solver(&self, alt_words: bool) -> Solver {
+        self.use_caches(Solver::new(alt_words))
+    }
+
+    /// Creates a solver with the given start word that uses the caches.
+    pub fn solver_with_start_word(
+        &self,
+        alt_words: bool,
+        start_word: &str,
+    ) -> Result<Solver, HmodeError> {
+        Ok(self.use_caches(Solver::new(alt_words).with_start_word(start_word)?))
+    }
+
+    fn use_caches(&self, solver: Solver) -> Solver {
+        let solver = solver.with_decision_cache(self.decisions.clone());
+        let start_word = solver.start_word();
+        let table = match &self.second_guesses {
+            Some(table) if table.start_word == start_word => Some(table.clone()),
+            _ if self.saved_second_guesses => {
+                second_guess::default_table(start_word, self.decisions.alt_words())
+            }
+            _ => None,
+        };
+        match table {
+            Some(table) => solver.with_second_guesses(table),
+            None => solver,
+        }
+    }
+}
+
+/// Solves every answer in the shard, or the whole answer list, with the given
+/// start word.
+pub fn solve_answers(
+    start_word: &str,
+    alt_words: bool,
+    shard: Option<Shard>,
+    caches: &SolverCaches,
+) -> Result<Vec<Solve>, HmodeError> {
+    let mut solves: Vec<Solve> = Vec::new();
+    let answers = shard::select(shard, words::answers());
+    let mut progress = Progress::new("Solving", answers.len());
+    let new_game = caches.solver_with_start_word(alt_words, start_word)?;
+    for &word in answers {
+        if progress::cancelled() {
+            break;
+        }
+        let mut solver = new_game.clone();
+        let setter = Setter::from_word(word);
+        let mut guess;
+        loop {
+            guess = solver.guess()?;
+            let result = setter.check(guess);
+            if let [Clue::Right(_), Clue::Right(_), Clue::Right(_), Clue::Right(_), Clue::Right(_)] =
+                result
+            {
+                solves.push(Solve(
+                    std::str::from_utf8(&guess).unwrap().into(),
+                    solver.guesses(),
+                ));
+                break;
+            }
+            solver.filter_self(result);
+        }
+        progress.inc();
+    }
+    progress.finish();
+    Ok(solves)
+}
+
+/// Scores each candidate start word by the average reduction in the answer
+/// list after the first guess, and returns the scores best first.
+pub fn find_optimal_start_word(
+    alt_words: bool,
+    shard: Option<Shard>,
+    mut checkpoint: Option<Checkpoint>,
+    resume: bool,
+) -> Result<Vec<StartWordRanking>, HmodeError> {
+    let answer_words = words::answers();
+    let initial_answer_count = answer_words.len() as f32;
+    let mut rankings: Vec<StartWordRanking> = match &checkpoint {
+        Some(c) if resume => c.load()?,
+        Some(c) => {
+            c.check_unused()?;
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+    let scored: HashSet<String> = rankings.iter().map(|r| r.0.clone()).collect();
+    let candidates: Vec<String> = shard::select(shard, words::all(alt_words))
+        .into_iter()
+        .map(|w| String::from_utf8_lossy(w).into_owned())
+        .filter(|w| !scored.contains(w))
+        .collect();
+    let mut progress = Progress::new("Ranking", candidates.len());
+
+    // Iterate through all candidate words
+    for start_word_str in candidates {
+        if progress::cancelled() {
+            break;
+        }
+        
+        let mut total_reduction: f32 = 0.0;
+        let new_game = Solver::new(alt_words).with_start_word(&start_word_str)?;
+        
+        // For each answer word, calculate the reduction in answer list size
+        for &answer_word in answer_words {
+            let mut solver = new_game.clone();
+            let setter = Setter::from_word(answer_word);
+            
+            // Get the first guess
+            let guess = solver.guess()?;
+            
+            // Check the guess against the setter
+            let result = setter.check(guess);
+            
+            // Filter the solver with the result
+            solver.filter_self(result);
+            
+            // Calculate the reduction in answer list size
+            let remaining_count = solver.remaining() as f32;
+            let reduction = initial_answer_count - remaining_count;
+            total_reduction += reduction;
+        }
+        
+        // Calculate average reduction
+        let average_reduction = total_reduction / answer_words.len() as f32;
+
+        // Keep every score so that it can be saved in the checkpoint
+        rankings.push(StartWordRanking(start_word_str, average_reduction));
+        if let Some(c) = &mut checkpoint {
+            c.save_if_due(&rankings)?;
+        }
+        
+        progress.inc();
+    }
+    progress.finish();
+    if let Some(c) = &mut checkpoint {
+        c.save(&rankings)?;
+    }
+    
+    // Sort with the best words first
+    rankings.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    
+    Ok(rankings)
+}
+
+pub struct FullGameRanking {
+    pub word: String,
+    pub reduction: f32,
+    pub stats: Stats,
+}
+
+/// Ranks a shortlist of start words by the statistics of full games over the
+/// whole answer list. The best words have the lowest mean solve length, with
+/// ties broken by the maximum solve length and then by the number of fails.
+pub fn rank_full_games(
+    shortlist: &[StartWordRanking],
+    alt_words: bool,
+    caches: &SolverCaches,
+) -> Result<Vec<FullGameRanking>, HmodeError> {
+    let mut rankings = Vec::new();
+    for (i, candidate) in shortlist.iter().enumerate() {
+        eprintln!(
+            "Full game stats for {} ({}/{})",
+            candidate.0,
+            i + 1,
+            shortlist.len()
+        );
+        let solves = solve_answers(&candidate.0, alt_words, None, caches)?;
+        if progress::cancelled() {
+            // The stats for this word are incomplete, so leave it out
+            break;
+        }
+        rankings.push(FullGameRanking {
+            word: candidate.0.clone(),
+            reduction: candidate.1,
+            stats: Stats::from_solves(&solves),
+        });
+    }
+    rankings.sort_by(|a, b| {
+        a.stats
+            .mean
+            .partial_cmp(&b.stats.mean)
+            .unwrap()
+            .then(a.stats.max.cmp(&b.stats.max))
+            .then(a.stats.fails().cmp(&b.stats.fails()))
+    });
+    Ok(rankings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn stats_display() {
+        let outliers: Vec<Outlier> =
+            [Outlier("table".into(), 7), Outlier("fable".into(), 7)].into();
+        let stats = Stats {
+            mean: 3.14,
+            max: 7,
+            outliers,
+        };
+        println!("{}", stats);
+    }
+}
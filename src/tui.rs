@@ -0,0 +1,543 @@
+use crate::colour::Palette;
+use hmode::{
+    CheckResult, Clue, HmodeError, MAX_GUESSES, Recorder, RuleSet, Setter, Solver, WdlWord,
+    is_solved, parse_pattern, to_static_word,
+};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
+
+/// The number of solver hints shown.
+const HINT_COUNT: usize = 5;
+
+const KEYBOARD_ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// How often the screen is checked for finished hints while they are searched for.
+const HINT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Who knows the answer: hmode, in play mode, or a game being played
+/// elsewhere, in assist mode, where the user enters the clues received.
+pub enum Mode {
+    Play(Setter),
+    Assist,
+}
+
+impl Mode {
+    fn prompt(&self) -> &'static str {
+        match self {
+            Mode::Play(_) => "Type a guess and press Enter",
+            Mode::Assist => "Type the word guessed, or press Enter for the first hint",
+        }
+    }
+}
+
+// The solver's suggestions for the current position, which are searched for
+// on another thread so that the interface stays responsive
+enum Hints {
+    NotSearched,
+    Searching(Receiver<Vec<WdlWord>>),
+    Found(Vec<WdlWord>),
+}
+
+/// The state of a game shown in the terminal interface, updated by key presses.
+pub struct App {
+    mode: Mode,
+    rules: RuleSet,
+    alt_words: bool,
+    solver: Solver,
+    history: Vec<CheckResult>,
+    recorder: Option<Recorder>,
+    // The letters of the guess being typed
+    input: String,
+    // In assist mode, the guess whose clue pattern is being typed
+    pattern_for: Option<WdlWord>,
+    pattern: String,
+    message: String,
+    show_hints: bool,
+    hints: Hints,
+    // In assist mode, whether Enter was pressed for the first hint before the
+    // hints were found
+    take_hint: bool,
+    finished: bool,
+    palette: Palette,
+}
+
+impl App {
+    pub fn new(mode: Mode, solver: Solver, alt_words: bool, rules: RuleSet) -> Self {
+        let message = mode.prompt();
+        App {
+            mode,
+            rules,
+            alt_words,
+            solver,
+            history: Vec::new(),
+            recorder: None,
+            input: String::new(),
+            pattern_for: None,
+            pattern: String::new(),
+            message: message.into(),
+            show_hints: false,
+            hints: Hints::NotSearched,
+            take_hint: false,
+            finished: false,
+            palette: Palette::Standard,
+        }
+    }
+
+    /// Records the session to the given recorder.
+    pub fn with_recorder(mut self, recorder: Option<Recorder>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+
+    /// Colours the tiles with the given palette.
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Runs the interface until the user quits, restoring the terminal after.
+    pub fn run(mut self) -> Result<(), HmodeError> {
+        let mut terminal = ratatui::init();
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<(), HmodeError> {
+        let io = |e| HmodeError::io("drawing the terminal interface", e);
+        loop {
+            self.receive_hints(false);
+            terminal.draw(|frame| self.draw(frame)).map_err(io)?;
+            // While hints are searched for, the screen is redrawn until they arrive
+            if matches!(self.hints, Hints::Searching(_))
+                && !event::poll(HINT_POLL_INTERVAL).map_err(io)?
+            {
+                continue;
+            }
+            if let Event::Key(key) = event::read().map_err(io)?
+                && key.kind == KeyEventKind::Press
+                && !self.handle_key(key)?
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Updates the game for a key press. Returns false when the user quits.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Result<bool, HmodeError> {
+        let ctrl_c =
+            key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c');
+        if key.code == KeyCode::Esc || ctrl_c {
+            return Ok(false);
+        }
+        if self.finished {
+            return Ok(true);
+        }
+        if key.code == KeyCode::Tab {
+            self.show_hints = !self.show_hints;
+            if self.show_hints {
+                self.search_hints();
+            }
+            return Ok(true);
+        }
+        if self.pattern_for.is_some() {
+            self.pattern_key(key.code)?;
+        } else {
+            self.word_key(key.code)?;
+        }
+        Ok(true)
+    }
+
+    fn word_key(&mut self, code: KeyCode) -> Result<(), HmodeError> {
+        match code {
+            KeyCode::Char(c) if c.is_ascii_alphabetic() && self.input.len() < 5 => {
+                self.stop_taking_hint();
+                self.input.push(c.to_ascii_lowercase());
+            }
+            KeyCode::Backspace => {
+                self.stop_taking_hint();
+                self.input.pop();
+            }
+            KeyCode::Enter if self.input.is_empty() => {
+                self.take_hint = true;
+                self.search_hints();
+                self.receive_hints(false);
+                if self.take_hint {
+                    self.message = "Thinking…".into();
+                }
+            }
+            KeyCode::Enter => self.submit_word()?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn pattern_key(&mut self, code: KeyCode) -> Result<(), HmodeError> {
+        match code {
+            KeyCode::Char(c) if self.pattern.len() < 5 => match c.to_ascii_uppercase() {
+                'G' | 'Y' | '-' => self.pattern.push(c.to_ascii_uppercase()),
+                ' ' | '.' => self.pattern.push('-'),
+                _ => {}
+            },
+            KeyCode::Backspace if self.pattern.is_empty() => {
+                // Go back to change the word
+                self.pattern_for = None;
+                self.message = "Type the word guessed".into();
+            }
+            KeyCode::Backspace => {
+                self.pattern.pop();
+            }
+            KeyCode::Enter => self.submit_pattern()?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn submit_word(&mut self) -> Result<(), HmodeError> {
+        let guess = match to_static_word(&self.input, false, self.alt_words)
+            .and_then(|guess| self.rules.check(&self.history, guess).map(|_| guess))
+        {
+            Ok(guess) => guess,
+            Err(e) => {
+                self.message = e.to_string();
+                return Ok(());
+            }
+        };
+        match &self.mode {
+            Mode::Play(setter) => {
+                let result = setter.check(guess);
+                self.add_clue(guess, result)
+            }
+            Mode::Assist => {
+                self.pattern_for = Some(guess);
+                self.pattern.clear();
+                self.message =
+                    "Type the clue pattern, G (right place), Y (elsewhere) or - (not in word)"
+                        .into();
+                Ok(())
+            }
+        }
+    }
+
+    fn submit_pattern(&mut self) -> Result<(), HmodeError> {
+        let Some(guess) = self.pattern_for else {
+            return Ok(());
+        };
+        let result = match parse_pattern(guess, &self.pattern) {
+            Ok(result) => result,
+            Err(e) => {
+                self.message = e.to_string();
+                return Ok(());
+            }
+        };
+        // Clues that rule out every answer are most likely a typing mistake
+        let mut next = self.solver.clone();
+        next.record_guess(guess, result);
+        if let Err(e) = next.check_clues() {
+            self.message = format!("{e}. Check the clue and enter it again");
+            return Ok(());
+        }
+        self.pattern_for = None;
+        self.add_clue(guess, result)
+    }
+
+    fn add_clue(&mut self, guess: WdlWord, result: CheckResult) -> Result<(), HmodeError> {
+        self.solver.record_guess(guess, result);
+        self.history.push(result);
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(guess, &result, self.solver.remaining())?;
+        }
+        self.input.clear();
+        // Any search for the previous position's hints is abandoned
+        self.hints = Hints::NotSearched;
+        if self.show_hints {
+            self.search_hints();
+        }
+        let guesses = self.history.len();
+        if is_solved(&result) {
+            self.finished = true;
+            self.message = format!("Solved in {guesses} guesses. Press Esc to quit");
+        } else if let Mode::Play(setter) = &self.mode
+            && guesses >= MAX_GUESSES
+        {
+            self.finished = true;
+            self.message = format!(
+                "The word was {}. Press Esc to quit",
+                String::from_utf8_lossy(&setter.word()).to_uppercase()
+            );
+        } else {
+            self.message = self.mode.prompt().into();
+        }
+        Ok(())
+    }
+
+    // Starts searching for the solver's suggestions, which are only searched
+    // for once per position
+    fn search_hints(&mut self) {
+        if let Hints::NotSearched = self.hints {
+            let solver = self.solver.clone();
+            let (sender, receiver) = mpsc::channel();
+            std::thread::spawn(move || {
+                // The receiver is gone if the position changed during the search
+                _ = sender.send(solver.suggestions(HINT_COUNT).unwrap_or_default());
+            });
+            self.hints = Hints::Searching(receiver);
+        }
+    }
+
+    // Collects the hints if the search has finished, or waits for it to
+    // finish, and fills in the first hint if Enter was pressed for it
+    fn receive_hints(&mut self, wait: bool) {
+        if let Hints::Searching(receiver) = &self.hints {
+            let hints = if wait {
+                Some(receiver.recv().unwrap_or_default())
+            } else {
+                match receiver.try_recv() {
+                    Ok(hints) => Some(hints),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => Some(Vec::new()),
+                }
+            };
+            if let Some(hints) = hints {
+                self.hints = Hints::Found(hints);
+            }
+        }
+        if self.take_hint
+            && let Hints::Found(hints) = &self.hints
+        {
+            if let Some(hint) = hints.first() {
+                self.input = String::from_utf8_lossy(hint).into_owned();
+            }
+            self.stop_taking_hint();
+        }
+    }
+
+    fn stop_taking_hint(&mut self) {
+        if self.take_hint {
+            self.take_hint = false;
+            self.message = self.mode.prompt().into();
+        }
+    }
+
+    /// The best clue received so far for each letter, for colouring the keyboard.
+    pub fn letter_clues(&self) -> [Option<Clue>; 26] {
+        let mut clues = [None; 26];
+        let rank = |clue: Option<Clue>| match clue {
+            Some(Clue::Right(_)) => 3,
+            Some(Clue::Elsewhere(_)) => 2,
+            Some(Clue::Wrong(_)) => 1,
+            None => 0,
+        };
+        for &clue in self.history.iter().flatten() {
+            let (Clue::Right(c) | Clue::Elsewhere(c) | Clue::Wrong(c)) = clue;
+            let entry = &mut clues[(c - b'a') as usize];
+            if rank(Some(clue)) > rank(*entry) {
+                *entry = Some(clue);
+            }
+        }
+        clues
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let rows = (self.history.len() + 1).max(MAX_GUESSES) as u16;
+        let [title, board, status, hints, keyboard, help] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(rows),
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Length(4),
+            Constraint::Min(1),
+        ])
+        .areas(frame.area());
+
+        let heading = match self.mode {
+            Mode::Play(_) => "hmode : play",
+            Mode::Assist => "hmode : assist",
+        };
+        frame.render_widget(
+            Paragraph::new(heading.bold()).alignment(Alignment::Center),
+            title,
+        );
+        frame.render_widget(
+            Paragraph::new(self.board_lines(rows as usize)).alignment(Alignment::Center),
+            board,
+        );
+        let status_lines = vec![
+            Line::from(format!("{} possible answers", self.solver.remaining())),
+            Line::from(self.message.clone()),
+        ];
+        frame.render_widget(
+            Paragraph::new(status_lines).alignment(Alignment::Center),
+            status,
+        );
+        if self.show_hints {
+            let text = match &self.hints {
+                Hints::NotSearched | Hints::Searching(_) => "Thinking…".to_string(),
+                Hints::Found(hints) if hints.is_empty() => "No hints".to_string(),
+                Hints::Found(hints) => hints
+                    .iter()
+                    .map(|h| String::from_utf8_lossy(h).into_owned())
+                    .collect::<Vec<_>>()
+                    .join("  "),
+            };
+            frame.render_widget(
+                Paragraph::new(format!("Hints : {text}")).alignment(Alignment::Center),
+                hints,
+            );
+        }
+        frame.render_widget(
+            Paragraph::new(self.keyboard_lines())
+                .alignment(Alignment::Center)
+                .block(Block::new()),
+            keyboard,
+        );
+        frame.render_widget(
+            Paragraph::new("Tab : show or hide hints   Esc : quit".dark_gray())
+                .alignment(Alignment::Center),
+            help,
+        );
+    }
+
+    fn board_lines(&self, rows: usize) -> Vec<Line<'static>> {
+        let mut lines: Vec<Line> = self
+            .history
+            .iter()
+            .map(|result| {
+                Line::from(
+                    result
+                        .iter()
+                        .map(|clue| clue_tile(*clue, self.palette))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        if !self.finished {
+            lines.push(self.input_line());
+        }
+        while lines.len() < rows {
+            lines.push(Line::from(vec![tile(' ', Style::new().on_dark_gray()); 5]));
+        }
+        lines
+    }
+
+    // The row being typed. In assist mode, once the word has been entered, its
+    // tiles are coloured as the pattern is typed.
+    fn input_line(&self) -> Line<'static> {
+        let mut tiles = Vec::with_capacity(5);
+        if let Some(guess) = self.pattern_for {
+            let pattern = self.pattern.as_bytes();
+            for (i, &c) in guess.iter().enumerate() {
+                tiles.push(match pattern.get(i) {
+                    Some(b'G') => clue_tile(Clue::Right(c), self.palette),
+                    Some(b'Y') => clue_tile(Clue::Elsewhere(c), self.palette),
+                    Some(_) => clue_tile(Clue::Wrong(c), self.palette),
+                    None => tile(c as char, Style::new().white().on_black()),
+                });
+            }
+        } else {
+            let mut letters = self.input.chars();
+            for _ in 0..5 {
+                let c = letters.next().unwrap_or(' ');
+                tiles.push(tile(c, Style::new().white().on_black()));
+            }
+        }
+        Line::from(tiles)
+    }
+
+    fn keyboard_lines(&self) -> Vec<Line<'static>> {
+        let clues = self.letter_clues();
+        KEYBOARD_ROWS
+            .iter()
+            .map(|row| {
+                Line::from(
+                    row.bytes()
+                        .map(|c| match clues[(c - b'a') as usize] {
+                            Some(clue) => clue_tile(clue, self.palette),
+                            None => tile(c as char, Style::new().black().on_gray()),
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn tile(c: char, style: Style) -> Span<'static> {
+    Span::styled(format!(" {} ", c.to_ascii_uppercase()), style)
+}
+
+fn clue_tile(clue: Clue, palette: Palette) -> Span<'static> {
+    let (Clue::Right(c) | Clue::Elsewhere(c) | Clue::Wrong(c)) = clue;
+    let style = Style::new()
+        .fg(Color::Indexed(palette.foreground(clue)))
+        .bg(Color::Indexed(palette.background(clue)));
+    tile(c as char, style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn type_keys(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '<' => KeyCode::Backspace,
+                '\t' => KeyCode::Tab,
+                c => KeyCode::Char(c),
+            };
+            assert!(app.handle_key(KeyEvent::from(code)).unwrap());
+            app.receive_hints(true);
+        }
+    }
+
+    #[test]
+    fn play_colours_board_and_keyboard() {
+        let setter = Setter::from_word(*b"crook");
+        let mut app = App::new(Mode::Play(setter), Solver::new(false), false, RuleSet::Hard);
+        type_keys(&mut app, "xyzzy\n");
+        assert!(app.message.contains("not in the list"));
+        type_keys(&mut app, "<<<<<tares\n");
+        assert_eq!(app.history.len(), 1);
+        let clues = app.letter_clues();
+        assert_eq!(clues[(b'r' - b'a') as usize], Some(Clue::Elsewhere(b'r')));
+        assert_eq!(clues[(b't' - b'a') as usize], Some(Clue::Wrong(b't')));
+        assert_eq!(clues[(b'c' - b'a') as usize], None);
+        type_keys(&mut app, "\t");
+        assert!(matches!(&app.hints, Hints::Found(hints) if hints.len() == HINT_COUNT));
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("possible answers"));
+        assert!(screen.contains(" T  A  R  E  S "));
+        assert!(screen.contains("Hints : "));
+    }
+
+    #[test]
+    fn assist_rejects_contradictory_clues() {
+        let mut app = App::new(Mode::Assist, Solver::new(false), false, RuleSet::Hard);
+        type_keys(&mut app, "\n");
+        assert_eq!(app.input, "tares");
+        type_keys(&mut app, "\nggggy\n");
+        assert!(app.history.is_empty());
+        assert!(app.message.contains("No answer word matches"));
+        type_keys(&mut app, "<<<<<---y-\n");
+        assert_eq!(app.history.len(), 1);
+        assert!(!app.finished);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::assistant::Assistant;
+use crate::error::HmodeError;
+use crate::setter::RuleSet;
+use wasm_bindgen::prelude::*;
+
+/// The solver for JavaScript, for running the assistant in a browser. It is
+/// fed the guesses and clue patterns from a game played elsewhere, e.g.
+///   const solver = new Solver(false, true);
+///   solver.feedClue("tares", "--Y--");
+///   solver.suggestions(5);
+#[wasm_bindgen(js_name = Solver)]
+pub struct WasmSolver {
+    assistant: Assistant,
+}
+
+#[wasm_bindgen(js_class = Solver)]
+impl WasmSolver {
+    /// Creates a solver for a new game, using the alternative word lists if
+    /// `alt_words` is set and enforcing the hard mode rules if `hard_mode` is.
+    #[wasm_bindgen(constructor)]
+    pub fn new(alt_words: bool, hard_mode: bool) -> WasmSolver {
+        let rules = if hard_mode {
+            RuleSet::Hard
+        } else {
+            RuleSet::Normal
+        };
+        WasmSolver {
+            assistant: Assistant::new(alt_words, rules),
+        }
+    }
+
+    /// Records a guess and its clue pattern of G, Y and -, e.g. "--Y--". A
+    /// clue that breaks the rules or leaves no possible answer is rejected,
+    /// and the solver is left as it was.
+    #[wasm_bindgen(js_name = feedClue)]
+    pub fn feed_clue(&mut self, guess: &str, pattern: &str) -> Result<(), JsError> {
+        self.assistant
+            .feed_clue(guess, pattern)
+            .map(|_| ())
+            .map_err(js_error)
+    }
+
+    /// The solver's best guesses, best first. The first is the guess the
+    /// solver would make itself.
+    pub fn suggestions(&self, count: usize) -> Result<Vec<String>, JsError> {
+        let suggestions = self.assistant.suggestions(count).map_err(js_error)?;
+        Ok(suggestions
+            .iter()
+            .map(|w| String::from_utf8_lossy(w).into_owned())
+            .collect())
+    }
+
+    /// The number of answer words that match the clues so far.
+    pub fn remaining(&self) -> usize {
+        self.assistant.remaining()
+    }
+}
+
+fn js_error(e: HmodeError) -> JsError {
+    JsError::new(&e.to_string())
+}
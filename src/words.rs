@@ -1,10 +1,17 @@
+use crate::error::HmodeError;
+
+/// A word from the word list, as 5 lowercase ASCII letters.
 pub type WdlWord = [u8; 5];
 
+/// The solver's first guess, unless another start word is chosen.
 pub static DEFAULT_START_WORD: WdlWord = *b"tares";
 
 // Import the static word lists generated by build.rs
 include!(concat!(env!("OUT_DIR"), "/words-generated.rs"));
 
+/// Returns every word that can be guessed, with the answer words first. The
+/// alternative list has far fewer obscure words.
+#[allow(clippy::needless_borrow)]
 pub fn all(alt_words: bool) -> &'static [WdlWord] {
     if alt_words {
         &ALT_WORDS
@@ -13,34 +20,148 @@ pub fn all(alt_words: bool) -> &'static [WdlWord] {
     }
 }
 
+/// Returns the words that can be answers.
 pub fn answers() -> &'static [WdlWord] {
     &ALL_WORDS[0..ANSWER_WORDS_END]
 }
 
-/// Validates that the provided word is in the list of all allowed
-/// words, and returns a static reference to the word in the list.
-/// This simplifies lifetime management for client code.
-pub fn to_static_word(word: &str, answers_only: bool, alt_words: bool) -> Result<WdlWord, String> {
+/// Finds words in a word list in O(log n) time, using a sorted copy of the
+/// list generated by build.rs.
+#[derive(Debug, Clone, Copy)]
+pub struct WordIndex {
+    words: &'static [WdlWord],
+    sorted: &'static [(WdlWord, u16)],
+}
+
+impl WordIndex {
+    /// The index of the list returned by `all(alt_words)`.
+    pub fn new(alt_words: bool) -> Self {
+        if alt_words {
+            WordIndex {
+                words: ALT_WORDS,
+                sorted: ALT_WORDS_INDEX,
+            }
+        } else {
+            WordIndex {
+                words: ALL_WORDS,
+                sorted: ALL_WORDS_INDEX,
+            }
+        }
+    }
+
+    /// Returns the position of a word in the word list.
+    pub fn position(&self, word: &WdlWord) -> Option<usize> {
+        let i = self.sorted.partition_point(|(w, _)| w < word);
+        self.sorted
+            .get(i)
+            .filter(|(w, _)| w == word)
+            .map(|(_, position)| *position as usize)
+    }
+
+    pub fn contains(&self, word: &WdlWord) -> bool {
+        self.position(word).is_some()
+    }
+
+    /// Returns true if the word can be an answer.
+    pub fn is_answer(&self, word: &WdlWord) -> bool {
+        self.position(word)
+            .is_some_and(|position| position < ANSWER_WORDS_END)
+    }
+
+    /// Returns the word at a position in the word list.
+    pub fn word(&self, position: usize) -> Option<WdlWord> {
+        self.words.get(position).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// Returns a word as text. Words from the word lists are always ASCII, so
+/// "?????" is only given for other bytes.
+pub fn word_str(word: &WdlWord) -> &str {
+    std::str::from_utf8(word).unwrap_or("?????")
+}
+
+/// Trims a word entered by a user and converts it to lowercase, rejecting
+/// anything that isn't 5 ASCII letters.
+pub fn normalise(word: &str) -> Result<String, HmodeError> {
+    let word = word.trim();
+    if !word.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(HmodeError::NonAlphabetic(word.into()));
+    }
     if word.len() != 5 {
-        return Err("Word must have 5 letters".into());
+        return Err(HmodeError::WrongLength(word.into()));
     }
-    let list = if answers_only {
-        answers()
-    } else {
-        all(alt_words)
-    };
+    Ok(word.to_ascii_lowercase())
+}
+
+/// The number of single letter insertions, deletions, substitutions or swaps of
+/// adjacent letters needed to turn one word into the other.
+fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j - 1] + cost)
+                .min(d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+const MAX_SUGGESTIONS: usize = 3;
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Returns the words in the list nearest to a misspelt word, closest first.
+/// Answer words come before other words at the same distance.
+pub fn suggestions(word: &str, list: &[WdlWord]) -> Vec<String> {
+    let word = word.trim().to_ascii_lowercase();
+    let mut near: Vec<(usize, &WdlWord)> = list
+        .iter()
+        .map(|w| (edit_distance(word.as_bytes(), w), w))
+        .filter(|(d, _)| *d <= MAX_SUGGESTION_DISTANCE)
+        .collect();
+    // The sort is stable, so the list order is kept for equal distances
+    near.sort_by_key(|(d, _)| *d);
+    near.iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, w)| String::from_utf8_lossy(*w).into_owned())
+        .collect()
+}
+
+/// Validates that the provided word is in the list of all allowed
+/// words, and returns a static reference to the word in the list.
+/// This simplifies lifetime management for client code. The word is
+/// normalised first, so surrounding spaces and capital letters are accepted.
+pub fn to_static_word(
+    word: &str,
+    answers_only: bool,
+    alt_words: bool,
+) -> Result<WdlWord, HmodeError> {
+    let word = normalise(word)?;
     // Copy the letters in word into a byte buffer as we can't convert
     // a str to an array of bytes directly
     let mut temp: [u8; 5] = [0; 5];
     word.bytes().zip(temp.iter_mut()).for_each(|(b, p)| *p = b);
-    // Return the matching word in the static
-    // probe word list, or an error if it's not in the list.
-    if let Some(entry) = list.iter().find(|w| **w == temp) {
-        Ok(*entry)
-    } else {
-        Err(format!(
-            "The word '{word}' is not in the list of valid words"
-        ))
+    // The answer words come first in both lists
+    match WordIndex::new(alt_words).position(&temp) {
+        Some(position) if !answers_only || position < ANSWER_WORDS_END => Ok(temp),
+        Some(_) => Err(HmodeError::NotAnAnswer(word)),
+        None => Err(HmodeError::NotInList { word, alt_words }),
     }
 }
 
@@ -70,4 +191,51 @@ mod tests {
     fn to_static_word_detects_probe_word_used_as_answer_word() {
         to_static_word("caber", true, false).unwrap();
     }
+
+    #[test]
+    fn to_static_word_reports_kind_of_error() {
+        let error = |word| to_static_word(word, true, false).unwrap_err();
+        assert_eq!(error("wibble"), HmodeError::WrongLength("wibble".into()));
+        assert!(matches!(error("xxxxx"), HmodeError::NotInList { .. }));
+        assert_eq!(error("caber"), HmodeError::NotAnAnswer("caber".into()));
+        assert_eq!(error("cr4ne"), HmodeError::NonAlphabetic("cr4ne".into()));
+    }
+
+    #[test]
+    fn to_static_word_normalises_input() {
+        assert_eq!(to_static_word(" TARES\n", false, false), Ok(*b"tares"));
+        assert_eq!(
+            to_static_word("crème", false, false),
+            Err(HmodeError::NonAlphabetic("crème".into()))
+        );
+    }
+
+    #[test]
+    fn misspelt_words_get_suggestions() {
+        assert_eq!(edit_distance(b"crane", b"crnae"), 1);
+        assert_eq!(edit_distance(b"crane", b"cane"), 1);
+        assert_eq!(edit_distance(b"crane", b"crane"), 0);
+        let error = to_static_word("crnae", true, false).unwrap_err();
+        assert_eq!(error.suggestions()[0], "crane");
+        assert!(error.to_string().contains("Did you mean crane"));
+        // Suggestions come from the whole list, even where an answer is needed
+        let error = to_static_word("cabee", true, false).unwrap_err();
+        assert!(error.suggestions().contains(&"caber".to_string()));
+        assert!(suggestions("qqqqq", all(false)).is_empty());
+    }
+
+    #[test]
+    fn word_index_finds_every_word() {
+        for alt_words in [false, true] {
+            let index = WordIndex::new(alt_words);
+            assert_eq!(index.len(), all(alt_words).len());
+            for (position, word) in all(alt_words).iter().enumerate() {
+                assert_eq!(index.position(word), Some(position));
+                assert_eq!(index.word(position), Some(*word));
+            }
+            assert!(!index.contains(b"xxxxx"));
+            assert!(index.is_answer(b"crook"));
+            assert!(!index.is_answer(b"caber"));
+        }
+    }
 }